@@ -25,6 +25,21 @@ pub fn cntvct_el0() -> u64 {
     cnt
 }
 
+/// Reads the virtual count after an `ISB`, so that it is not read speculatively.
+#[inline(always)]
+pub fn cntvct_el0_serialized() -> u64 {
+    let cnt: u64;
+    unsafe {
+        core::arch::asm!(
+            "isb",
+            "mrs {cnt}, cntvct_el0",
+            cnt = lateout(reg) cnt,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+    cnt
+}
+
 #[inline(always)]
 pub fn cntpct_el0() -> u64 {
     let cnt: u64;
@@ -1,16 +1,63 @@
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use std::sync::OnceLock;
+
+/// How long the TSC is timed against the OS clock, when the CPU does not report its
+/// frequency.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+const CALIBRATION_NANOS: u64 = 10_000_000;
+
 /// Checks whether the CPU has an invariant TSC.
 #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
 #[inline]
 pub fn has_counter_support() -> bool {
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        use raw_cpuid::CpuId;
-    }
+    return raw_cpuid::CpuId::new()
+        .get_advanced_power_mgmt_info()
+        .is_some_and(|apm| apm.has_invariant_tsc());
 
     #[cfg(target_arch = "aarch64")]
     return true;
 }
 
+/// Returns the frequency of the TSC in hertz.
+///
+/// The frequency is taken from the CPUID leaf 0x15, or the base frequency of leaf 0x16,
+/// which is the TSC frequency of CPUs with an invariant TSC. Otherwise, as on most AMD
+/// CPUs and virtual machines, the TSC is timed against the OS clock once.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn tsc_frequency() -> u64 {
+    static FREQUENCY: OnceLock<u64> = OnceLock::new();
+    *FREQUENCY.get_or_init(|| cpuid_tsc_frequency().unwrap_or_else(calibrate_tsc_frequency))
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn cpuid_tsc_frequency() -> Option<u64> {
+    let cpuid = raw_cpuid::CpuId::new();
+    if let Some(frequency) = cpuid.get_tsc_info().and_then(|info| info.tsc_frequency()) {
+        return Some(frequency);
+    }
+    let base_megas = cpuid
+        .get_processor_frequency_info()
+        .map_or(0, |info| info.processor_base_frequency());
+    (base_megas > 0).then(|| base_megas as u64 * crate::metrics::freq::MEGAS_IN_HERTZ)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn calibrate_tsc_frequency() -> u64 {
+    use crate::arch::read_cpu_counter_serialized;
+    use crate::metrics::time::NANOS_PER_SEC;
+    use crate::os::read_os_time;
+
+    let os_start = read_os_time();
+    let tsc_start = read_cpu_counter_serialized();
+    let mut os_elapsed = 0;
+    while os_elapsed < CALIBRATION_NANOS {
+        os_elapsed = read_os_time() - os_start;
+    }
+    let tsc_elapsed = read_cpu_counter_serialized() - tsc_start;
+    (tsc_elapsed as u128 * NANOS_PER_SEC as u128 / os_elapsed as u128) as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -20,4 +67,19 @@ mod tests {
     fn test_aarch64_has_counter_support() {
         assert!(has_counter_support());
     }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_calibrated_tsc_frequency_matches_cpuid() {
+        let calibrated = calibrate_tsc_frequency();
+        assert!(calibrated > 0);
+        // CPUs that report the frequency should agree with the OS clock within 10%.
+        if let Some(reported) = cpuid_tsc_frequency() {
+            let ratio = calibrated as f64 / reported as f64;
+            assert!(
+                (0.9..1.1).contains(&ratio),
+                "{calibrated} Hz vs {reported} Hz"
+            );
+        }
+    }
 }
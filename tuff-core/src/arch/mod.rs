@@ -1,21 +1,29 @@
 #[cfg(target_arch = "aarch64")]
 pub mod aarch64;
 
+#[cfg(target_arch = "x86_64")]
+pub mod x86_64;
+
 pub mod detect;
 
 #[cfg(test)]
 mod test;
 
+use crate::metrics::{Frequency, MetricType, TickUnit, TimeSource};
+
 #[cfg(target_arch = "x86")]
-use core::arch::x86::_rdtsc;
+use core::arch::x86::{_mm_lfence, _rdtsc};
 
 #[cfg(target_arch = "x86_64")]
-use core::arch::x86_64::_rdtsc;
+use core::arch::x86_64::{_mm_lfence, _rdtsc};
 
 /// Returns the frequency of the CPU counter.
 #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
 #[inline]
 pub fn read_cpu_counter_frequency() -> u64 {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    return crate::arch::detect::tsc_frequency();
+
     #[cfg(target_arch = "aarch64")]
     return crate::arch::aarch64::cntfrq_el0();
 }
@@ -36,3 +44,74 @@ pub fn read_cpu_counter() -> u64 {
     #[cfg(target_arch = "aarch64")]
     return crate::arch::aarch64::cntvct_el0();
 }
+
+/// Reads the CPU timer, or timestamp counter (TSC), in program order.
+///
+/// The plain read can be executed speculatively, before the preceding instructions have
+/// completed. On `x86` the read is fenced with `LFENCE` on both sides, and on `aarch64`
+/// it is preceded by an `ISB`, at the cost of a slower read.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+#[inline]
+pub fn read_cpu_counter_serialized() -> u64 {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    return unsafe {
+        _mm_lfence();
+        let counter = _rdtsc();
+        _mm_lfence();
+        counter
+    };
+
+    #[cfg(target_arch = "aarch64")]
+    return crate::arch::aarch64::cntvct_el0_serialized();
+}
+
+/// The CPU counter as a [`TimeSource`], read with [`read_cpu_counter`].
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuCounter;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+impl TimeSource for CpuCounter {
+    #[inline(always)]
+    fn read() -> u64 {
+        read_cpu_counter()
+    }
+
+    fn unit() -> TickUnit {
+        TickUnit::Cycles
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::read()
+    }
+
+    fn metric_type() -> Option<MetricType> {
+        Some(MetricType::CpuCounter)
+    }
+}
+
+/// The CPU counter as a [`TimeSource`], read in program order with
+/// [`read_cpu_counter_serialized`].
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuCounterSerialized;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+impl TimeSource for CpuCounterSerialized {
+    #[inline(always)]
+    fn read() -> u64 {
+        read_cpu_counter_serialized()
+    }
+
+    fn unit() -> TickUnit {
+        TickUnit::Cycles
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::read()
+    }
+
+    fn metric_type() -> Option<MetricType> {
+        Some(MetricType::CpuCounterSerialized)
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(target_os = "linux")]
+use crate::os::linux::perf::PerfEventMmapPage;
+
+/// Reads the performance-monitoring counter with the given index.
+///
+/// # Safety
+///
+/// Raises a general protection fault unless the OS allows user space `rdpmc` (`CR4.PCE`),
+/// and `counter` is a valid counter index for the CPU.
+#[inline(always)]
+pub unsafe fn rdpmc(counter: u32) -> u64 {
+    let low: u32;
+    let high: u32;
+    unsafe {
+        core::arch::asm!(
+            "rdpmc",
+            in("ecx") counter,
+            lateout("eax") low,
+            lateout("edx") high,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+    ((high as u64) << 32) | low as u64
+}
+
+/// Reads a perf event counter from user space with `rdpmc`, without a system call.
+///
+/// Follows the seqlock protocol documented in `include/uapi/linux/perf_event.h`: the
+/// `index`, `offset` and `pmc_width` fields are re-read until `lock` is unchanged.
+/// Returns `None` when the kernel does not allow `rdpmc` for the event, or the event is
+/// not currently scheduled on a hardware counter; the caller should fall back to `read(2)`.
+///
+/// # Safety
+///
+/// `page` must point to the first page of a live perf event mmap of the calling thread.
+#[cfg(target_os = "linux")]
+#[inline(always)]
+pub unsafe fn read_perf_counter(page: *const PerfEventMmapPage) -> Option<u64> {
+    use core::ptr::{addr_of, read_volatile};
+    use core::sync::atomic::{Ordering, compiler_fence};
+
+    loop {
+        let seq = unsafe { read_volatile(addr_of!((*page).lock)) };
+        compiler_fence(Ordering::SeqCst);
+
+        let capabilities = unsafe { read_volatile(addr_of!((*page).capabilities)) };
+        let index = unsafe { read_volatile(addr_of!((*page).index)) };
+        if !PerfEventMmapPage::cap_user_rdpmc(capabilities) || index == 0 {
+            return None;
+        }
+        let offset = unsafe { read_volatile(addr_of!((*page).offset)) };
+        let width = unsafe { read_volatile(addr_of!((*page).pmc_width)) } as u32;
+        // Kernels that do not report the width can not be read with `rdpmc` either.
+        if width == 0 || width > 64 {
+            return None;
+        }
+
+        // The hardware counter is `pmc_width` bits wide; sign extend it before adding it.
+        let shift = 64 - width;
+        let pmc = ((unsafe { rdpmc(index - 1) } << shift) as i64) >> shift;
+        let count = offset.wrapping_add(pmc) as u64;
+
+        compiler_fence(Ordering::SeqCst);
+        if unsafe { read_volatile(addr_of!((*page).lock)) } == seq {
+            return Some(count);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Microbenchmarks timed with the low-overhead metric sources.
+//!
+//! Each benchmark is warmed up, which also estimates the cost of an iteration, so that
+//! every sample runs enough iterations to rise well above the resolution of the source.
+//! The per-iteration samples are summarised with outlier-robust statistics, and all the
+//! benchmarks can be turned into a [`ProfileReport`].
+use std::hint::black_box;
+use std::io::{Write, stdout};
+use tabwriter::{Alignment, TabWriter};
+
+use crate::error::Result;
+use crate::metrics::time::{NANOS_PER_MILLI, NANOS_PER_SEC};
+use crate::metrics::{Duration, MAX_EXTRA_METRICS, MetricType, ProfileMetric};
+use crate::os::read_os_time;
+use crate::report::fmt::format_index;
+use crate::report::{Measurement, ProfileReport};
+
+pub mod repetition;
+pub use repetition::{RepetitionResults, RepetitionTester};
+
+pub mod stats;
+pub use stats::Statistics;
+
+#[cfg(test)]
+mod test;
+
+const DEFAULT_WARM_UP_NANOS: u64 = 500 * NANOS_PER_MILLI;
+const DEFAULT_MEASUREMENT_NANOS: u64 = 2 * NANOS_PER_SEC;
+const DEFAULT_SAMPLE_COUNT: usize = 100;
+
+/// Runs benchmarks and collects their results.
+#[derive(Debug)]
+pub struct Bencher {
+    metric_type: MetricType,
+    warm_up_time: Duration,
+    measurement_time: Duration,
+    sample_count: usize,
+    results: Vec<BenchResult>,
+}
+
+impl Bencher {
+    pub fn new(metric_type: MetricType) -> Self {
+        Self {
+            metric_type,
+            warm_up_time: Duration::from_nanos(DEFAULT_WARM_UP_NANOS),
+            measurement_time: Duration::from_nanos(DEFAULT_MEASUREMENT_NANOS),
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            results: Vec::new(),
+        }
+    }
+
+    /// Sets how long each benchmark runs before it is measured.
+    pub fn warm_up_time(mut self, warm_up_time: Duration) -> Self {
+        self.warm_up_time = warm_up_time;
+        self
+    }
+
+    /// Sets the approximate time spent measuring each benchmark.
+    pub fn measurement_time(mut self, measurement_time: Duration) -> Self {
+        self.measurement_time = measurement_time;
+        self
+    }
+
+    /// Sets the number of samples taken for each benchmark.
+    ///
+    /// Panics if the number of samples is zero.
+    pub fn sample_count(mut self, sample_count: usize) -> Self {
+        assert!(sample_count > 0, "At least one sample is required");
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub fn results(&self) -> &[BenchResult] {
+        &self.results
+    }
+
+    /// Benchmarks a function without input.
+    pub fn bench_function<O>(
+        &mut self,
+        label: &'static str,
+        mut f: impl FnMut() -> O,
+    ) -> &BenchResult {
+        self.bench_with_input(label, &(), |_| f())
+    }
+
+    /// Benchmarks a function with an input, hidden from the optimiser with `black_box`.
+    pub fn bench_with_input<I: ?Sized, O>(
+        &mut self,
+        label: &'static str,
+        input: &I,
+        mut f: impl FnMut(&I) -> O,
+    ) -> &BenchResult {
+        // Warm up in batches of doubling size, which also estimates the cost of an iteration.
+        let warm_up_start = read_os_time();
+        let mut warm_up_iterations = 0;
+        let mut batch = 1;
+        let warm_up_elapsed = loop {
+            for _ in 0..batch {
+                black_box(f(black_box(input)));
+            }
+            warm_up_iterations += batch;
+            batch *= 2;
+            let elapsed = read_os_time() - warm_up_start;
+            if elapsed >= self.warm_up_time.as_nanos() {
+                break elapsed;
+            }
+        };
+
+        let iteration_nanos = std::cmp::max(warm_up_elapsed / warm_up_iterations, 1);
+        let sample_nanos = self.measurement_time.as_nanos() / self.sample_count as u64;
+        let iterations_per_sample = std::cmp::max(sample_nanos / iteration_nanos, 1);
+
+        let mut samples = Vec::with_capacity(self.sample_count);
+        let mut total = 0;
+        for _ in 0..self.sample_count {
+            let start = self.metric_type.read();
+            for _ in 0..iterations_per_sample {
+                black_box(f(black_box(input)));
+            }
+            let elapsed = self.metric_type.read() - start;
+            total += elapsed;
+            samples.push(elapsed as f64 / iterations_per_sample as f64);
+        }
+
+        self.results.push(BenchResult {
+            label,
+            metric_type: self.metric_type,
+            iterations_per_sample,
+            statistics: Statistics::new(&samples),
+            samples,
+            total,
+        });
+        self.results.last().unwrap()
+    }
+
+    /// Converts the results into a report, with one measurement per benchmark.
+    pub fn report(&self) -> ProfileReport {
+        let total = self.results.iter().map(|result| result.total).sum();
+        let mut report = ProfileReport::new(
+            self.metric_type.to_metric(0),
+            self.metric_type.to_metric(total),
+        );
+        for (anchor_index, result) in self.results.iter().enumerate() {
+            report.push_measurement(result.measurement(anchor_index));
+        }
+        report
+    }
+
+    pub fn print(&self) -> Result<()> {
+        let stdout = stdout().lock();
+        let mut tabwriter = TabWriter::new(stdout).alignment(Alignment::Right);
+        writeln!(
+            &mut tabwriter,
+            "\n{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            format_index("Label"),
+            format_index("Iterations"),
+            format_index("Median"),
+            format_index("MAD"),
+            format_index("CI Low"),
+            format_index("CI High"),
+            format_index("Mean"),
+            format_index("Minimum"),
+            format_index("Maximum"),
+        )?;
+        for result in &self.results {
+            let stats = &result.statistics;
+            writeln!(
+                &mut tabwriter,
+                "{}\t{}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}",
+                format_index(result.label),
+                result.iterations(),
+                stats.median,
+                stats.mad,
+                stats.confidence_interval.0,
+                stats.confidence_interval.1,
+                stats.mean,
+                stats.min,
+                stats.max,
+            )?;
+        }
+        Ok(tabwriter.flush()?)
+    }
+}
+
+impl Default for Bencher {
+    fn default() -> Self {
+        Self::new(MetricType::OsClock)
+    }
+}
+
+/// Samples and statistics of a single benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    label: &'static str,
+    metric_type: MetricType,
+    iterations_per_sample: u64,
+
+    /// Metric elapsed per iteration, one value per sample.
+    samples: Vec<f64>,
+
+    /// Metric elapsed over all the samples.
+    total: u64,
+    statistics: Statistics,
+}
+
+impl BenchResult {
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn iterations_per_sample(&self) -> u64 {
+        self.iterations_per_sample
+    }
+
+    /// Total number of measured iterations.
+    pub fn iterations(&self) -> u64 {
+        self.iterations_per_sample * self.samples.len() as u64
+    }
+
+    /// Metric elapsed per iteration, one value per sample.
+    pub fn samples(&self) -> &[f64] {
+        &self.samples
+    }
+
+    /// Metric elapsed per iteration as `Counter` or `Duration`, rounded to whole units.
+    pub fn sample_metrics(&self) -> impl Iterator<Item = ProfileMetric> + '_ {
+        self.samples
+            .iter()
+            .map(|sample| self.metric_type.to_metric(sample.round() as u64))
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    fn measurement(&self, anchor_index: usize) -> Measurement {
+        let total = self.metric_type.to_metric(self.total);
+        Measurement {
+            label: self.label,
+            anchor_index,
+            parent: None,
+            hit_count: self.iterations(),
+            elapsed_exclusive: total,
+            elapsed_inclusive: total,
+            elapsed_min: self
+                .metric_type
+                .to_metric(self.statistics.min.round() as u64),
+            elapsed_max: self
+                .metric_type
+                .to_metric(self.statistics.max.round() as u64),
+            processed_bytes: 0,
+            extra: [None; MAX_EXTRA_METRICS],
+            elapsed_wall: None,
+            completion_count: 0,
+            histogram: None,
+        }
+    }
+}
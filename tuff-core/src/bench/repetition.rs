@@ -0,0 +1,170 @@
+use std::io::{Write, stdout};
+
+use crate::error::Result;
+use crate::metrics::time::NANOS_PER_SEC;
+use crate::metrics::{Counter, Duration, Frequency};
+use crate::report::fmt::{format_index, format_number};
+use crate::report::rep::gigabytes_per_second;
+
+/// Runs the same block repeatedly, until its minimum time stops improving.
+///
+/// Where a benchmark looks for a representative time, the repetition tester looks for
+/// the best case: how fast the code can run once caches, branch predictors and the page
+/// tables are warm. Every new minimum restarts the wait, so the test ends only after the
+/// minimum has not improved for the whole `try_for` duration.
+#[derive(Debug)]
+pub struct RepetitionTester {
+    frequency: Frequency,
+    try_for: Duration,
+    byte_count: u64,
+    print_progress: bool,
+}
+
+impl RepetitionTester {
+    /// Creates a tester for a block that processes `byte_count` bytes per repetition.
+    pub fn new(byte_count: u64, try_for: Duration) -> Self {
+        Self {
+            frequency: Frequency::read(),
+            try_for,
+            byte_count,
+            print_progress: true,
+        }
+    }
+
+    /// Sets whether every new minimum is printed while the test runs.
+    pub fn print_progress(mut self, print_progress: bool) -> Self {
+        self.print_progress = print_progress;
+        self
+    }
+
+    /// Runs the block until its minimum time has not improved for `try_for`.
+    pub fn run(&mut self, mut f: impl FnMut()) -> Result<RepetitionResults> {
+        let try_for = Counter::from_cycles(
+            (self.try_for.as_nanos() as u128 * self.frequency.in_hertz() as u128
+                / NANOS_PER_SEC as u128) as u64,
+        );
+
+        let mut results = RepetitionResults {
+            test_count: 0,
+            total: Counter::default(),
+            min: Counter::from_cycles(u64::MAX),
+            max: Counter::default(),
+            frequency: self.frequency,
+            byte_count: self.byte_count,
+        };
+
+        let mut stdout = stdout().lock();
+        let mut min_found_at = Counter::read();
+        loop {
+            let start = Counter::read();
+            f();
+            let end = Counter::read();
+            let elapsed = end - start;
+
+            results.test_count += 1;
+            results.total = results.total + elapsed;
+            results.max = std::cmp::max(results.max, elapsed);
+            if elapsed < results.min {
+                results.min = elapsed;
+                min_found_at = end;
+                if self.print_progress {
+                    write!(
+                        &mut stdout,
+                        "\r{} {}    ",
+                        format_index("Min:"),
+                        results.format_counter(elapsed)
+                    )?;
+                    stdout.flush()?;
+                }
+            } else if end - min_found_at > try_for {
+                break;
+            }
+        }
+        if self.print_progress {
+            writeln!(&mut stdout)?;
+        }
+        Ok(results)
+    }
+}
+
+/// Minimum, maximum and average time and bandwidth of a repetition test.
+#[derive(Debug, Clone, Copy)]
+pub struct RepetitionResults {
+    test_count: u64,
+    total: Counter,
+    min: Counter,
+    max: Counter,
+    frequency: Frequency,
+    byte_count: u64,
+}
+
+impl RepetitionResults {
+    pub fn test_count(&self) -> u64 {
+        self.test_count
+    }
+
+    pub fn min(&self) -> Counter {
+        self.min
+    }
+
+    pub fn max(&self) -> Counter {
+        self.max
+    }
+
+    pub fn average(&self) -> Counter {
+        Counter::from_cycles(self.total.cycles() / std::cmp::max(self.test_count, 1))
+    }
+
+    /// Bandwidth in GB/s of a repetition that took `counter` cycles.
+    ///
+    /// Not measured if the repetition took no cycles, as it can on a coarse counter.
+    pub fn bandwidth(&self, counter: Counter) -> Option<f64> {
+        let seconds = counter.cycles() as f64 / self.frequency.in_hertz() as f64;
+        gigabytes_per_second(self.byte_count, seconds)
+    }
+
+    fn format_counter(&self, counter: Counter) -> String {
+        let duration = counter.to_duration(self.frequency);
+        let mut formatted = format!(
+            "{} ({:.3}ms)",
+            format_number(self.min.cycles(), self.max.cycles(), counter.cycles()),
+            duration.as_nanos() as f64 / 1e6,
+        );
+        if self.byte_count > 0 {
+            match self.bandwidth(counter) {
+                Some(bandwidth) => formatted.push_str(&format!(" {bandwidth:.3}GB/s")),
+                None => formatted.push_str(" - GB/s"),
+            }
+        }
+        formatted
+    }
+
+    pub fn print(&self) -> Result<()> {
+        let mut stdout = stdout().lock();
+        writeln!(
+            &mut stdout,
+            "{} {}",
+            format_index("Min:"),
+            self.format_counter(self.min)
+        )?;
+        writeln!(
+            &mut stdout,
+            "{} {}",
+            format_index("Max:"),
+            self.format_counter(self.max)
+        )?;
+        writeln!(
+            &mut stdout,
+            "{} {}",
+            format_index("Avg:"),
+            self.format_counter(self.average())
+        )?;
+        writeln!(
+            &mut stdout,
+            "{} {}",
+            format_index("Repetitions:"),
+            self.test_count
+        )?;
+        Ok(())
+    }
+}
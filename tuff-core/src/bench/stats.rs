@@ -0,0 +1,90 @@
+/// Number of resamples used to estimate the confidence interval of the median.
+const BOOTSTRAP_RESAMPLES: usize = 1_000;
+
+/// Confidence level of the bootstrap interval.
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Outlier-robust statistics of per-iteration samples, in units of the metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Statistics {
+    pub mean: f64,
+    pub median: f64,
+
+    /// Median absolute deviation from the median.
+    pub mad: f64,
+    pub min: f64,
+    pub max: f64,
+
+    /// Bootstrap confidence interval of the median, at `CONFIDENCE_LEVEL`.
+    pub confidence_interval: (f64, f64),
+}
+
+impl Statistics {
+    /// Computes the statistics of the samples.
+    ///
+    /// Panics if there are no samples.
+    pub fn new(samples: &[f64]) -> Self {
+        assert!(!samples.is_empty(), "No samples to compute statistics");
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let median = median_sorted(&sorted);
+
+        let mut deviations: Vec<f64> = sorted.iter().map(|s| (s - median).abs()).collect();
+        deviations.sort_by(f64::total_cmp);
+
+        Self {
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+            median,
+            mad: median_sorted(&deviations),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            confidence_interval: bootstrap_median(&sorted),
+        }
+    }
+}
+
+pub(crate) fn median_sorted(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Percentile bootstrap of the median.
+fn bootstrap_median(samples: &[f64]) -> (f64, f64) {
+    let mut rng = XorShift::new(0x9e37_79b9_7f4a_7c15);
+    let mut resample = vec![0.0; samples.len()];
+    let mut medians = Vec::with_capacity(BOOTSTRAP_RESAMPLES);
+    for _ in 0..BOOTSTRAP_RESAMPLES {
+        for value in resample.iter_mut() {
+            *value = samples[rng.next_below(samples.len())];
+        }
+        resample.sort_by(f64::total_cmp);
+        medians.push(median_sorted(&resample));
+    }
+    medians.sort_by(f64::total_cmp);
+
+    let tail = (1.0 - CONFIDENCE_LEVEL) / 2.0;
+    let lower = (tail * (BOOTSTRAP_RESAMPLES - 1) as f64).round() as usize;
+    let upper = ((1.0 - tail) * (BOOTSTRAP_RESAMPLES - 1) as f64).round() as usize;
+    (medians[lower], medians[upper])
+}
+
+/// A small, seeded PRNG, so that the bootstrap is reproducible.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
@@ -0,0 +1,72 @@
+use super::{Bencher, RepetitionTester, Statistics};
+use crate::metrics::{Counter, Duration, MetricType};
+
+#[test]
+fn test_statistics_median_and_mad() {
+    let stats = Statistics::new(&[5.0, 1.0, 3.0, 2.0, 100.0]);
+    assert_eq!(stats.median, 3.0);
+    assert_eq!(stats.mad, 2.0);
+    assert_eq!(stats.min, 1.0);
+    assert_eq!(stats.max, 100.0);
+    assert_eq!(stats.mean, 22.2);
+
+    let stats = Statistics::new(&[4.0, 1.0, 3.0, 2.0]);
+    assert_eq!(stats.median, 2.5);
+}
+
+#[test]
+fn test_statistics_confidence_interval() {
+    let constant = Statistics::new(&[7.0; 50]);
+    assert_eq!(constant.confidence_interval, (7.0, 7.0));
+
+    let samples: Vec<f64> = (0..101).map(f64::from).collect();
+    let stats = Statistics::new(&samples);
+    let (lower, upper) = stats.confidence_interval;
+    assert!(lower <= stats.median && stats.median <= upper);
+    assert!(lower >= stats.min && upper <= stats.max);
+}
+
+#[test]
+fn test_bencher_collects_samples() {
+    let mut bencher = Bencher::new(MetricType::CpuCounter)
+        .warm_up_time(Duration::from_nanos(1_000_000))
+        .measurement_time(Duration::from_nanos(5_000_000))
+        .sample_count(10);
+
+    let input = vec![1u64; 1024];
+    let result = bencher.bench_with_input("sum", &input, |input| input.iter().sum::<u64>());
+    assert_eq!(result.label(), "sum");
+    assert_eq!(result.samples().len(), 10);
+    assert!(result.iterations_per_sample() >= 1);
+    assert!(result.statistics().median > 0.0);
+    let iterations = result.iterations();
+
+    bencher.bench_function("noop", || ());
+    let report = bencher.report();
+    assert_eq!(report.measurements.len(), 2);
+    assert_eq!(report.measurements[0].hit_count, iterations);
+}
+
+#[test]
+fn test_repetition_tester_finds_minimum() {
+    let data = vec![1u8; 4096];
+    let mut tester = RepetitionTester::new(data.len() as u64, Duration::from_nanos(5_000_000))
+        .print_progress(false);
+    let results = tester
+        .run(|| {
+            std::hint::black_box(data.iter().map(|b| *b as u64).sum::<u64>());
+        })
+        .unwrap();
+
+    assert!(results.test_count() > 1);
+    assert!(results.min() <= results.average());
+    assert!(results.average() <= results.max());
+    if let (Some(fastest), Some(slowest)) = (
+        results.bandwidth(results.min()),
+        results.bandwidth(results.max()),
+    ) {
+        assert!(fastest >= slowest);
+    }
+    // A repetition shorter than a tick has no bandwidth, instead of an infinite one.
+    assert_eq!(results.bandwidth(Counter::from_cycles(0)), None);
+}
@@ -0,0 +1,182 @@
+//! Characterisation of the metric sources on the running machine.
+//!
+//! Before picking a [`MetricType`] it helps to know how fine-grained and how costly each
+//! source is. For every source this measures the smallest observable tick, the mean
+//! latency of a read, and the jitter between back-to-back reads.
+use std::hint::black_box;
+use std::io::{Write, stdout};
+use tabwriter::{Alignment, TabWriter};
+
+use crate::error::Result;
+use crate::metrics::time::NANOS_PER_SEC;
+use crate::metrics::{Frequency, MetricType};
+use crate::os::read_os_time;
+use crate::report::fmt::format_index;
+
+/// Number of reads timed to estimate the read latency and jitter.
+const READS: usize = 10_000;
+
+/// Number of ticks observed to estimate the minimum tick.
+const TICKS: usize = 1_000;
+
+/// Maximum number of reads to wait for a single tick, for sources that may never change.
+const MAX_READS_PER_TICK: usize = 1_000_000;
+
+/// Maximum time spent observing ticks, for coarse sources whose ticks are milliseconds apart.
+const MAX_TICK_SEARCH_NANOS: u64 = 100_000_000;
+
+/// Resolution and read cost of a metric source.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceCalibration {
+    pub metric_type: MetricType,
+
+    /// Smallest non-zero difference between two consecutive reads, in source units.
+    /// `None` if the source did not change while it was observed.
+    pub min_tick: Option<u64>,
+
+    /// Mean cost of a single read, in nanoseconds.
+    pub mean_read_latency_ns: f64,
+
+    /// Mean difference between two back-to-back reads, in source units.
+    pub mean_read_delta: f64,
+
+    /// Standard deviation of the difference between two back-to-back reads, in source units.
+    pub jitter: f64,
+
+    /// Rate at which the source advanced during the calibration, against the OS clock.
+    pub frequency: Frequency,
+}
+
+/// Calibrations of several metric sources.
+#[derive(Debug, Clone)]
+pub struct Calibration {
+    pub sources: Vec<SourceCalibration>,
+}
+
+/// Measures the resolution and read cost of a single metric source.
+pub fn characterise(metric_type: MetricType) -> SourceCalibration {
+    // Warm up the source, some open their counters on the first read.
+    black_box(metric_type.read());
+
+    let os_start = read_os_time();
+    let source_start = metric_type.read();
+
+    let mut deltas = Vec::with_capacity(READS);
+    let mut prev = metric_type.read();
+    for _ in 0..READS {
+        let curr = black_box(metric_type.read());
+        deltas.push(curr.wrapping_sub(prev));
+        prev = curr;
+    }
+    let os_reads_end = read_os_time();
+
+    let mut min_tick = None;
+    let tick_search_start = read_os_time();
+    'ticks: for _ in 0..TICKS {
+        if read_os_time() - tick_search_start > MAX_TICK_SEARCH_NANOS {
+            break;
+        }
+        let start = metric_type.read();
+        for _ in 0..MAX_READS_PER_TICK {
+            let curr = metric_type.read();
+            if curr != start {
+                let tick = curr.wrapping_sub(start);
+                min_tick = Some(min_tick.map_or(tick, |min: u64| min.min(tick)));
+                continue 'ticks;
+            }
+        }
+        break;
+    }
+
+    let source_end = metric_type.read();
+    let os_end = read_os_time();
+
+    let mean_read_latency_ns = (os_reads_end - os_start) as f64 / (READS + 1) as f64;
+    let mean_read_delta = deltas.iter().sum::<u64>() as f64 / READS as f64;
+    let variance = deltas
+        .iter()
+        .map(|delta| (*delta as f64 - mean_read_delta).powi(2))
+        .sum::<f64>()
+        / READS as f64;
+    let frequency = Frequency::from_hertz(
+        ((source_end - source_start) as u128 * NANOS_PER_SEC as u128
+            / std::cmp::max(os_end - os_start, 1) as u128) as u64,
+    );
+
+    SourceCalibration {
+        metric_type,
+        min_tick,
+        mean_read_latency_ns,
+        mean_read_delta,
+        jitter: variance.sqrt(),
+        frequency,
+    }
+}
+
+/// Measures the resolution and read cost of several metric sources.
+pub fn characterise_sources(metric_types: &[MetricType]) -> Calibration {
+    Calibration {
+        sources: metric_types.iter().copied().map(characterise).collect(),
+    }
+}
+
+/// Measures the resolution and read cost of every clock source supported on this machine.
+pub fn characterise_all() -> Calibration {
+    #[allow(unused_mut)]
+    let mut metric_types = vec![
+        MetricType::OsClock,
+        MetricType::CoarseClock,
+        MetricType::CpuCounter,
+        MetricType::CpuCounterSerialized,
+    ];
+
+    #[cfg(target_os = "linux")]
+    {
+        use crate::metrics::PerfEventKind;
+        use crate::os::linux::perf::is_available;
+
+        for kind in [PerfEventKind::Cycles, PerfEventKind::TaskClock] {
+            if is_available(kind) {
+                metric_types.push(MetricType::PerfEvent(kind));
+            }
+        }
+    }
+
+    characterise_sources(&metric_types)
+}
+
+impl Calibration {
+    pub fn print(&self) -> Result<()> {
+        let stdout = stdout().lock();
+        let mut tabwriter = TabWriter::new(stdout).alignment(Alignment::Right);
+        writeln!(
+            &mut tabwriter,
+            "\n{}\t{}\t{}\t{}\t{}\t{}",
+            format_index("Source"),
+            format_index("Min Tick"),
+            format_index("Read Latency (ns)"),
+            format_index("Mean Delta"),
+            format_index("Jitter"),
+            format_index("Frequency (MHz)"),
+        )?;
+        for source in &self.sources {
+            let min_tick = source
+                .min_tick
+                .map_or_else(|| "-".to_string(), |tick| tick.to_string());
+            writeln!(
+                &mut tabwriter,
+                "{}\t{}\t{:.2}\t{:.2}\t{:.2}\t{}",
+                format_index(source.metric_type.name()),
+                min_tick,
+                source.mean_read_latency_ns,
+                source.mean_read_delta,
+                source.jitter,
+                source.frequency.in_megas(),
+            )?;
+        }
+        Ok(tabwriter.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod test;
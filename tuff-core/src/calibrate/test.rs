@@ -0,0 +1,28 @@
+use super::{characterise, characterise_all};
+use crate::metrics::MetricType;
+
+#[test]
+fn test_characterise_os_clock() {
+    let calibration = characterise(MetricType::OsClock);
+    assert_eq!(calibration.metric_type, MetricType::OsClock);
+    assert!(calibration.min_tick.is_some_and(|tick| tick > 0));
+    assert!(calibration.mean_read_latency_ns > 0.0);
+    assert!(calibration.jitter >= 0.0);
+    assert!(calibration.frequency.in_megas() > 0);
+}
+
+#[test]
+fn test_characterise_coarse_clock_is_bounded() {
+    let start = std::time::Instant::now();
+    let calibration = characterise(MetricType::CoarseClock);
+    assert!(calibration.min_tick.is_some_and(|tick| tick > 0));
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[test]
+fn test_characterise_all_includes_cpu_counters() {
+    let calibration = characterise_all();
+    let metric_types: Vec<_> = calibration.sources.iter().map(|s| s.metric_type).collect();
+    assert!(metric_types.contains(&MetricType::CpuCounter));
+    assert!(metric_types.contains(&MetricType::CpuCounterSerialized));
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::io;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors of the profiler and its reports.
+#[derive(Debug)]
+pub enum Error {
+    /// The profiler of the thread was never started.
+    NotStarted,
+
+    /// The profiler is still running, and a report requires it to be stopped.
+    NotStopped,
+
+    /// The profiler was started without a metric to record.
+    NoMetric,
+
+    /// The profiler was started with more metrics than it can record at once.
+    TooManyMetrics { max: usize },
+
+    /// Two values that should be in the same metric are in different ones.
+    MixedMetrics {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    /// A block was ended with [`crate::Profiler::end`] while a block opened after it was
+    /// still open.
+    MismatchedToken {
+        ended: &'static str,
+        innermost: &'static str,
+    },
+
+    /// A background reporter is already running; only one can run at a time.
+    ReporterRunning,
+
+    /// Writing a report failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotStarted => write!(f, "profiler not started"),
+            Self::NotStopped => write!(f, "profiler not stopped"),
+            Self::NoMetric => write!(f, "no metric to record"),
+            Self::TooManyMetrics { max } => {
+                write!(f, "too many metrics: at most {max} can be recorded at once")
+            }
+            Self::MixedMetrics { expected, found } => {
+                write!(f, "mixed metrics: expected {expected}, found {found}")
+            }
+            Self::MismatchedToken { ended, innermost } => write!(
+                f,
+                "ended block `{ended}`, which is not the innermost open block `{innermost}`"
+            ),
+            Self::ReporterRunning => write!(f, "a background reporter is already running"),
+            Self::Io(err) => write!(f, "failed to write report: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
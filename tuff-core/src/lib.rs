@@ -1,14 +1,32 @@
 pub mod arch;
-pub use arch::{read_cpu_counter, read_cpu_counter_frequency};
+pub use arch::{read_cpu_counter, read_cpu_counter_frequency, read_cpu_counter_serialized};
+
+pub mod bench;
+
+pub mod calibrate;
+
+pub mod error;
+pub use error::{Error, Result};
 
 pub mod metrics;
-pub use metrics::{Counter, Duration, Frequency, Instant};
+pub use metrics::{Counter, Duration, Frequency, Instant, TimeSource};
 
 pub mod os;
 pub use os::read_os_time;
 
 #[macro_use]
 pub mod profile;
-pub use profile::{CallSite, ProfileBlock, Profiler};
+pub use profile::{CallSite, DynamicLabel, Instrumented, ProfileBlock, Profiler};
 
 pub mod report;
+
+#[cfg(feature = "reporter")]
+pub mod reporter;
+#[cfg(feature = "reporter")]
+pub use reporter::Reporter;
+
+pub mod stopwatch;
+pub use stopwatch::Stopwatch;
+
+#[cfg(feature = "tracing")]
+pub mod tracing;
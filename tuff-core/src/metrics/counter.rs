@@ -1,4 +1,6 @@
-use crate::arch::read_cpu_counter;
+use crate::arch::{read_cpu_counter, read_cpu_counter_serialized};
+use crate::metrics::time::NANOS_PER_SEC;
+use crate::metrics::{Duration, Frequency};
 use std::ops::{Add, Sub};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
@@ -19,7 +21,14 @@ impl Counter {
     }
 
     pub fn read_serializing() -> Self {
-        todo!()
+        let counter = read_cpu_counter_serialized();
+        Self(counter)
+    }
+
+    /// Converts the counter to a duration, given the frequency of the counter.
+    pub fn to_duration(&self, frequency: Frequency) -> Duration {
+        let nanos = self.0 as u128 * NANOS_PER_SEC as u128 / frequency.in_hertz() as u128;
+        Duration::from_nanos(nanos as u64)
     }
 }
 
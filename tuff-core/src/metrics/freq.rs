@@ -1,12 +1,16 @@
 use crate::arch::read_cpu_counter_frequency;
 
-const MEGAS_IN_HERTZ: u64 = 1_000_000;
+pub(crate) const MEGAS_IN_HERTZ: u64 = 1_000_000;
 const GIGAS_IN_HERTZ: u64 = 1_000_000_000;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Frequency(u64);
 
 impl Frequency {
+    pub fn from_hertz(hertz: u64) -> Self {
+        Self(hertz)
+    }
+
     pub fn read() -> Self {
         let freq = read_cpu_counter_frequency();
         Self(freq)
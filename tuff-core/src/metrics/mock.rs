@@ -0,0 +1,59 @@
+//! A clock that only moves when it is told to, for deterministic tests.
+//!
+//! Available in the crate's own tests, and to other crates with the `mock` feature.
+use std::cell::Cell;
+
+use crate::metrics::time::NANOS_PER_SEC;
+use crate::metrics::{Frequency, TickUnit, TimeSource};
+
+thread_local! {
+    static MOCK_NOW: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A [`TimeSource`] in nanoseconds that is advanced by hand.
+///
+/// Each thread has its own time, like each thread has its own profiler, so tests running
+/// in parallel do not affect each other.
+///
+/// ```ignore
+/// MockClock::set(0);
+/// Profiler::start_global_with::<MockClock>();
+/// {
+///     let _block = ProfileBlock::new("parse", 1);
+///     MockClock::advance(100);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockClock;
+
+impl MockClock {
+    /// Returns the current time of the calling thread's clock.
+    pub fn now() -> u64 {
+        MOCK_NOW.with(Cell::get)
+    }
+
+    /// Sets the current time of the calling thread's clock.
+    pub fn set(now: u64) {
+        MOCK_NOW.with(|mock_now| mock_now.set(now));
+    }
+
+    /// Moves the calling thread's clock forward by `ticks` nanoseconds.
+    pub fn advance(ticks: u64) {
+        MOCK_NOW.with(|mock_now| mock_now.set(mock_now.get() + ticks));
+    }
+}
+
+impl TimeSource for MockClock {
+    #[inline(always)]
+    fn read() -> u64 {
+        Self::now()
+    }
+
+    fn unit() -> TickUnit {
+        TickUnit::Nanoseconds
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::from_hertz(NANOS_PER_SEC)
+    }
+}
@@ -7,15 +7,163 @@ pub use freq::Frequency;
 pub mod counter;
 pub use counter::Counter;
 
+pub mod source;
+pub use source::{TickUnit, TimeSource};
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::MockClock;
+
+use crate::arch::{read_cpu_counter, read_cpu_counter_serialized};
+use crate::os::{read_os_time, read_os_time_coarse, read_perf_event};
+
+/// Maximum number of metrics recorded next to the primary one by a multi-metric profile.
+pub const MAX_EXTRA_METRICS: usize = 3;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MetricType {
     OsClock,
+    CoarseClock,
     CpuCounter,
     CpuCounterSerialized,
+    PerfEvent(PerfEventKind),
+}
+
+impl MetricType {
+    /// Reads the current value of the metric source.
+    #[inline(always)]
+    pub fn read(self) -> u64 {
+        match self {
+            Self::OsClock => read_os_time(),
+            Self::CoarseClock => read_os_time_coarse(),
+            Self::CpuCounter => read_cpu_counter(),
+            Self::CpuCounterSerialized => read_cpu_counter_serialized(),
+            Self::PerfEvent(kind) => read_perf_event(kind),
+        }
+    }
+
+    /// Wraps a raw value read from the metric source.
+    pub fn to_metric(self, value: u64) -> ProfileMetric {
+        match self {
+            Self::OsClock | Self::CoarseClock => {
+                ProfileMetric::OsClock(Duration::from_nanos(value))
+            }
+            Self::CpuCounter | Self::CpuCounterSerialized => {
+                ProfileMetric::CpuCounter(Counter::from_cycles(value))
+            }
+            Self::PerfEvent(kind) => ProfileMetric::PerfEvent(kind, value),
+        }
+    }
+
+    /// Returns a short name of the metric source.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OsClock => "OS Clock",
+            Self::CoarseClock => "OS Clock Coarse",
+            Self::CpuCounter => "CPU Counter",
+            Self::CpuCounterSerialized => "CPU Counter Serialized",
+            Self::PerfEvent(kind) => kind.name(),
+        }
+    }
+}
+
+/// Events that can be counted with the performance counters of the OS.
+///
+/// Currently only Linux, through `perf_event_open`, is supported.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PerfEventKind {
+    /// CPU cycles. Unlike the TSC, these are affected by frequency scaling.
+    Cycles,
+    /// Retired instructions.
+    Instructions,
+    /// Last level cache accesses.
+    CacheReferences,
+    /// Last level cache misses.
+    CacheMisses,
+    /// Retired branch instructions.
+    BranchInstructions,
+    /// Mispredicted branch instructions.
+    BranchMisses,
+    /// Time the thread was running on a CPU, in nanoseconds.
+    TaskClock,
+    /// Page faults.
+    PageFaults,
+    /// Context switches.
+    ContextSwitches,
+}
+
+impl PerfEventKind {
+    pub const COUNT: usize = 9;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cycles => "cycles",
+            Self::Instructions => "instructions",
+            Self::CacheReferences => "cache-references",
+            Self::CacheMisses => "cache-misses",
+            Self::BranchInstructions => "branch-instructions",
+            Self::BranchMisses => "branch-misses",
+            Self::TaskClock => "task-clock",
+            Self::PageFaults => "page-faults",
+            Self::ContextSwitches => "context-switches",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProfileMetric {
     OsClock(Duration),
     CpuCounter(Counter),
+    PerfEvent(PerfEventKind, u64),
+    /// Ticks of a time source of your own, with the frequency of the source.
+    Ticks(u64, Frequency),
+}
+
+impl ProfileMetric {
+    /// Returns the raw value of the metric, in the unit of its source.
+    pub fn value(&self) -> u64 {
+        match self {
+            Self::OsClock(d) => d.as_nanos(),
+            Self::CpuCounter(c) => c.cycles(),
+            Self::PerfEvent(_, count) => *count,
+            Self::Ticks(ticks, _) => *ticks,
+        }
+    }
+
+    /// Returns a metric of the same source, with another value.
+    pub(crate) fn with_value(&self, value: u64) -> Self {
+        match self {
+            Self::OsClock(_) => Self::OsClock(Duration::from_nanos(value)),
+            Self::CpuCounter(_) => Self::CpuCounter(Counter::from_cycles(value)),
+            Self::PerfEvent(kind, _) => Self::PerfEvent(*kind, value),
+            Self::Ticks(_, frequency) => Self::Ticks(value, *frequency),
+        }
+    }
+
+    /// Converts the metric to seconds, if it measures time.
+    ///
+    /// CPU counters are converted with the frequency of the counter, and the ticks of other
+    /// sources with their own frequency.
+    pub fn as_secs_f64(&self) -> Option<f64> {
+        match self {
+            Self::OsClock(d) => Some(d.as_nanos() as f64 / time::NANOS_PER_SEC as f64),
+            Self::CpuCounter(c) => Some(c.cycles() as f64 / Frequency::read().in_hertz() as f64),
+            Self::PerfEvent(PerfEventKind::TaskClock, nanos) => {
+                Some(*nanos as f64 / time::NANOS_PER_SEC as f64)
+            }
+            Self::PerfEvent(..) => None,
+            Self::Ticks(ticks, frequency) => Some(*ticks as f64 / frequency.in_hertz() as f64),
+        }
+    }
+
+    /// Returns a short name of the metric source, used in report headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OsClock(_) => "OS Clock",
+            Self::CpuCounter(_) => "CPU Counter",
+            Self::PerfEvent(kind, _) => kind.name(),
+            Self::Ticks(..) => "Ticks",
+        }
+    }
 }
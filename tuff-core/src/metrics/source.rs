@@ -0,0 +1,43 @@
+use crate::metrics::time::NANOS_PER_SEC;
+use crate::metrics::{Duration, Frequency, MetricType};
+
+/// Unit of the ticks of a [`TimeSource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickUnit {
+    /// Each tick is a nanosecond.
+    Nanoseconds,
+    /// Each tick is a cycle of a counter, converted to time with its frequency.
+    Cycles,
+}
+
+/// A monotonic source of time, read as a number of ticks.
+///
+/// The sources are zero-sized types, so code generic over a source reads it directly,
+/// without dispatching on the kind of source at runtime. Implementing it for a type of
+/// your own, for example a mock clock, lets it drive a [`Stopwatch`] or the profiler.
+///
+/// [`Stopwatch`]: crate::Stopwatch
+pub trait TimeSource {
+    /// Reads the current value of the source, in ticks.
+    fn read() -> u64;
+
+    /// Returns the unit of the ticks.
+    fn unit() -> TickUnit;
+
+    /// Returns the number of ticks per second.
+    fn frequency() -> Frequency;
+
+    /// Returns the metric type that reads the same values, for the sources of `tuff`.
+    ///
+    /// The profiler reports the values of these sources as their metric type, and those of
+    /// other sources in nanoseconds or in ticks of their own frequency.
+    fn metric_type() -> Option<MetricType> {
+        None
+    }
+
+    /// Converts a number of ticks of the source to a duration.
+    fn to_duration(ticks: u64) -> Duration {
+        let nanos = ticks as u128 * NANOS_PER_SEC as u128 / Self::frequency().in_hertz() as u128;
+        Duration::from_nanos(nanos as u64)
+    }
+}
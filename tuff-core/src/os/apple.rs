@@ -24,3 +24,13 @@ pub fn mach_absolute_time_nanos() -> u64 {
     let ns = (ticks as u128).saturating_mul(numer as u128) / (denom as u128);
     ns as u64
 }
+
+/// Returns `mach_approximate_time` in nanoseconds.
+///
+/// The approximate time is cheaper to read than `mach_absolute_time`, at a coarser resolution.
+pub fn mach_approximate_time_nanos() -> u64 {
+    let ticks = unsafe { mach_time::mach_approximate_time() };
+    let (numer, denom) = mach_timebase_info();
+    let ns = (ticks as u128).saturating_mul(numer as u128) / (denom as u128);
+    ns as u64
+}
@@ -0,0 +1,28 @@
+pub mod perf;
+
+/// Reads `CLOCK_MONOTONIC` in nanoseconds.
+///
+/// On Linux `clock_gettime` is served by the vDSO, so this does not enter the kernel.
+#[inline]
+pub fn clock_monotonic_nanos() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    (ts.tv_sec as u64) * crate::metrics::time::NANOS_PER_SEC + ts.tv_nsec as u64
+}
+
+/// Reads `CLOCK_MONOTONIC_COARSE` in nanoseconds.
+///
+/// The coarse clock is the time of the last timer interrupt, so it is cheaper to read than
+/// `CLOCK_MONOTONIC`, but only advances once per tick of the kernel, every 1 to 10 ms.
+#[inline]
+pub fn clock_monotonic_coarse_nanos() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_COARSE, &mut ts) };
+    (ts.tv_sec as u64) * crate::metrics::time::NANOS_PER_SEC + ts.tv_nsec as u64
+}
@@ -0,0 +1,289 @@
+//! Hardware and software counters through `perf_event_open(2)`.
+//!
+//! Every thread lazily opens one counter per [`PerfEventKind`] the first time it is read.
+//! On `x86_64` the counter's first page is mapped, so that hardware counters can be read
+//! from user space with `rdpmc` instead of a `read(2)` system call.
+//! The counters only count user-space events of the calling thread, which is what
+//! `perf_event_paranoid <= 2` allows unprivileged processes to do.
+//! When the kernel refuses to open a counter (restrictive `perf_event_paranoid`, no PMU
+//! in a virtual machine, seccomp, ...), the counter is marked as unavailable and reads
+//! return zero instead of failing.
+//!
+//! Resource: https://man7.org/linux/man-pages/man2/perf_event_open.2.html
+use std::cell::RefCell;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::ptr::NonNull;
+
+use crate::metrics::PerfEventKind;
+
+const PERF_TYPE_HARDWARE: u32 = 0;
+const PERF_TYPE_SOFTWARE: u32 = 1;
+
+const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+const PERF_COUNT_HW_CACHE_REFERENCES: u64 = 2;
+const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+const PERF_COUNT_HW_BRANCH_INSTRUCTIONS: u64 = 4;
+const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+
+const PERF_COUNT_SW_TASK_CLOCK: u64 = 1;
+const PERF_COUNT_SW_PAGE_FAULTS: u64 = 2;
+const PERF_COUNT_SW_CONTEXT_SWITCHES: u64 = 3;
+
+const PERF_ATTR_SIZE_VER0: u32 = 64;
+const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+const ATTR_FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+const ATTR_FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+const PERF_EVENT_PARANOID: &str = "/proc/sys/kernel/perf_event_paranoid";
+
+/// The first version (`PERF_ATTR_SIZE_VER0`) of `struct perf_event_attr`.
+///
+/// The kernel accepts older, shorter layouts, so there is no need to track the latest one.
+#[repr(C)]
+#[derive(Debug, Default)]
+struct PerfEventAttr {
+    type_: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+}
+
+impl PerfEventAttr {
+    fn new(kind: PerfEventKind) -> Self {
+        let (type_, config) = match kind {
+            PerfEventKind::Cycles => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CPU_CYCLES),
+            PerfEventKind::Instructions => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_INSTRUCTIONS),
+            PerfEventKind::CacheReferences => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CACHE_REFERENCES),
+            PerfEventKind::CacheMisses => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CACHE_MISSES),
+            PerfEventKind::BranchInstructions => {
+                (PERF_TYPE_HARDWARE, PERF_COUNT_HW_BRANCH_INSTRUCTIONS)
+            }
+            PerfEventKind::BranchMisses => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_BRANCH_MISSES),
+            PerfEventKind::TaskClock => (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_TASK_CLOCK),
+            PerfEventKind::PageFaults => (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_PAGE_FAULTS),
+            PerfEventKind::ContextSwitches => (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_CONTEXT_SWITCHES),
+        };
+        Self {
+            type_,
+            size: PERF_ATTR_SIZE_VER0,
+            config,
+            flags: ATTR_FLAG_EXCLUDE_KERNEL | ATTR_FLAG_EXCLUDE_HV,
+            ..Default::default()
+        }
+    }
+}
+
+/// The header of the first page of a perf event mmap, `struct perf_event_mmap_page`.
+///
+/// Only the fields needed to read the counter from user space are declared; the kernel
+/// owns the memory and updates it under the `lock` seqlock.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PerfEventMmapPage {
+    pub version: u32,
+    pub compat_version: u32,
+    pub lock: u32,
+    pub index: u32,
+    pub offset: i64,
+    pub time_enabled: u64,
+    pub time_running: u64,
+    pub capabilities: u64,
+    pub pmc_width: u16,
+    pub time_shift: u16,
+    pub time_mult: u32,
+    pub time_offset: u64,
+}
+
+impl PerfEventMmapPage {
+    const CAP_USER_RDPMC: u64 = 1 << 2;
+
+    /// Whether the kernel allows reading the counter with `rdpmc`.
+    #[inline(always)]
+    pub fn cap_user_rdpmc(capabilities: u64) -> bool {
+        capabilities & Self::CAP_USER_RDPMC != 0
+    }
+}
+
+/// A read-only, shared mapping of the first page of a perf event.
+#[derive(Debug)]
+struct PerfMmap {
+    page: NonNull<PerfEventMmapPage>,
+    len: usize,
+}
+
+impl PerfMmap {
+    fn new(fd: &OwnedFd) -> io::Result<Self> {
+        let len = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let addr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let page = NonNull::new(addr as *mut PerfEventMmapPage).expect("mmap returned null");
+        Ok(Self { page, len })
+    }
+}
+
+impl Drop for PerfMmap {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.page.as_ptr() as *mut libc::c_void, self.len) };
+    }
+}
+
+/// A counter opened with `perf_event_open` for the calling thread, on any CPU.
+///
+/// The counter is enabled on creation and closed when dropped.
+#[derive(Debug)]
+pub struct PerfCounter {
+    kind: PerfEventKind,
+    fd: OwnedFd,
+    mmap: Option<PerfMmap>,
+}
+
+impl PerfCounter {
+    /// Opens a counter of the given kind for the calling thread.
+    pub fn open(kind: PerfEventKind) -> io::Result<Self> {
+        let attr = PerfEventAttr::new(kind);
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                0 as libc::pid_t,
+                -1 as libc::c_int,
+                -1 as libc::c_int,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd as RawFd) };
+        // The mapping is only needed for `rdpmc`, reads fall back to `read(2)` without it.
+        let mmap = if cfg!(target_arch = "x86_64") {
+            PerfMmap::new(&fd).ok()
+        } else {
+            None
+        };
+        Ok(Self { kind, fd, mmap })
+    }
+
+    pub fn kind(&self) -> PerfEventKind {
+        self.kind
+    }
+
+    /// Checks whether the counter can currently be read from user space with `rdpmc`.
+    pub fn has_user_read(&self) -> bool {
+        self.mmap.as_ref().is_some_and(|mmap| {
+            let page = unsafe { mmap.page.as_ref() };
+            PerfEventMmapPage::cap_user_rdpmc(page.capabilities) && page.index != 0
+        })
+    }
+
+    /// Reads the current value of the counter.
+    ///
+    /// Uses `rdpmc` when the kernel allows it, and a `read(2)` system call otherwise.
+    #[inline]
+    pub fn read(&self) -> io::Result<u64> {
+        #[cfg(target_arch = "x86_64")]
+        if let Some(mmap) = &self.mmap
+            && let Some(value) =
+                unsafe { crate::arch::x86_64::read_perf_counter(mmap.page.as_ptr()) }
+        {
+            return Ok(value);
+        }
+        self.read_syscall()
+    }
+
+    /// Reads the current value of the counter with a `read(2)` system call.
+    #[inline]
+    pub fn read_syscall(&self) -> io::Result<u64> {
+        let mut value: u64 = 0;
+        let n = unsafe {
+            libc::read(
+                self.fd.as_raw_fd(),
+                &mut value as *mut u64 as *mut libc::c_void,
+                size_of::<u64>(),
+            )
+        };
+        if n != size_of::<u64>() as isize {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Default)]
+enum CounterSlot {
+    #[default]
+    Unopened,
+    Open(PerfCounter),
+    Unavailable,
+}
+
+thread_local! {
+    static THREAD_COUNTERS: RefCell<[CounterSlot; PerfEventKind::COUNT]> =
+        RefCell::new(Default::default());
+}
+
+/// Reads the calling thread's counter of the given kind, opening it on first use.
+///
+/// Returns zero if the counter can not be opened or read.
+#[inline]
+pub fn read_thread_counter(kind: PerfEventKind) -> u64 {
+    THREAD_COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+        let slot = &mut counters[kind as usize];
+        if let CounterSlot::Unopened = slot {
+            *slot = match PerfCounter::open(kind) {
+                Ok(counter) => CounterSlot::Open(counter),
+                Err(_) => CounterSlot::Unavailable,
+            };
+        }
+        match slot {
+            CounterSlot::Open(counter) => counter.read().unwrap_or(0),
+            _ => 0,
+        }
+    })
+}
+
+/// Marks the calling thread's counter of the given kind as if it failed to open.
+#[cfg(test)]
+pub(crate) fn set_unavailable(kind: PerfEventKind) {
+    THREAD_COUNTERS
+        .with(|counters| counters.borrow_mut()[kind as usize] = CounterSlot::Unavailable);
+}
+
+/// Checks whether a counter of the given kind can be opened by the calling thread.
+pub fn is_available(kind: PerfEventKind) -> bool {
+    read_thread_counter(kind);
+    THREAD_COUNTERS
+        .with(|counters| matches!(counters.borrow()[kind as usize], CounterSlot::Open(_)))
+}
+
+/// Returns the value of `/proc/sys/kernel/perf_event_paranoid`.
+///
+/// Unprivileged processes can count their own user-space events when the level is at most 2.
+pub fn paranoid_level() -> Option<i32> {
+    std::fs::read_to_string(PERF_EVENT_PARANOID)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
@@ -1,10 +1,122 @@
 #[cfg(target_os = "macos")]
 pub mod apple;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+use crate::metrics::time::NANOS_PER_SEC;
+use crate::metrics::{Frequency, MetricType, PerfEventKind, TickUnit, TimeSource};
+
 /// Returns an OS managed low resolution timer in nanoseconds.
 pub fn read_os_time() -> u64 {
     #[cfg(target_os = "macos")]
     return crate::os::apple::mach_absolute_time_nanos();
+
+    #[cfg(target_os = "linux")]
+    return crate::os::linux::clock_monotonic_nanos();
+}
+
+/// The OS monotonic clock as a [`TimeSource`], read with [`read_os_time`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsClock;
+
+impl TimeSource for OsClock {
+    #[inline(always)]
+    fn read() -> u64 {
+        read_os_time()
+    }
+
+    fn unit() -> TickUnit {
+        TickUnit::Nanoseconds
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::from_hertz(NANOS_PER_SEC)
+    }
+
+    fn metric_type() -> Option<MetricType> {
+        Some(MetricType::OsClock)
+    }
+}
+
+/// The coarse OS monotonic clock as a [`TimeSource`], read with [`read_os_time_coarse`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoarseClock;
+
+impl TimeSource for CoarseClock {
+    #[inline(always)]
+    fn read() -> u64 {
+        read_os_time_coarse()
+    }
+
+    fn unit() -> TickUnit {
+        TickUnit::Nanoseconds
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::from_hertz(NANOS_PER_SEC)
+    }
+
+    fn metric_type() -> Option<MetricType> {
+        Some(MetricType::CoarseClock)
+    }
+}
+
+/// The CPU time of the calling thread as a [`TimeSource`], read with the `task-clock`
+/// performance counter.
+///
+/// Unlike the other sources, it does not advance while the thread is not running.
+/// Reads zero when the counter is not available.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskClock;
+
+#[cfg(target_os = "linux")]
+impl TimeSource for TaskClock {
+    #[inline(always)]
+    fn read() -> u64 {
+        read_perf_event(PerfEventKind::TaskClock)
+    }
+
+    fn unit() -> TickUnit {
+        TickUnit::Nanoseconds
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::from_hertz(NANOS_PER_SEC)
+    }
+
+    fn metric_type() -> Option<MetricType> {
+        Some(MetricType::PerfEvent(PerfEventKind::TaskClock))
+    }
+}
+
+/// Returns an OS managed clock in nanoseconds, that is cheaper to read than
+/// [`read_os_time`] but only advances every few milliseconds.
+///
+/// Reads `CLOCK_MONOTONIC_COARSE` on Linux, and `mach_approximate_time` on MacOS.
+pub fn read_os_time_coarse() -> u64 {
+    #[cfg(target_os = "macos")]
+    return crate::os::apple::mach_approximate_time_nanos();
+
+    #[cfg(target_os = "linux")]
+    return crate::os::linux::clock_monotonic_coarse_nanos();
+}
+
+/// Reads the calling thread's performance counter of the given kind.
+///
+/// Performance counters are only supported on Linux. Returns zero when the counter is
+/// not available, for example because `perf_event_paranoid` forbids access.
+#[inline]
+pub fn read_perf_event(kind: PerfEventKind) -> u64 {
+    #[cfg(target_os = "linux")]
+    return crate::os::linux::perf::read_thread_counter(kind);
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = kind;
+        0
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use super::read_os_time;
+use super::{read_os_time, read_os_time_coarse};
 
 #[test]
 fn test_os_timer() {
@@ -12,3 +12,60 @@ fn test_os_timer_not_zero() {
     let time = read_os_time();
     assert!(time > 0, "OS timer should return a positive value");
 }
+
+#[test]
+fn test_os_coarse_timer() {
+    let time_1 = read_os_time_coarse();
+    let time_2 = read_os_time_coarse();
+    assert!(time_2 >= time_1, "Coarse OS timer should be monotonic");
+    assert!(time_1 > 0, "Coarse OS timer should return a positive value");
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use crate::metrics::PerfEventKind;
+    use crate::os::linux::perf::{PerfCounter, is_available, read_thread_counter, set_unavailable};
+    use crate::os::read_perf_event;
+
+    #[test]
+    fn test_perf_event_monotonic() {
+        let kind = PerfEventKind::TaskClock;
+        let count_1 = read_perf_event(kind);
+        let count_2 = read_perf_event(kind);
+        assert!(count_2 >= count_1, "Perf counter should be monotonic");
+    }
+
+    #[test]
+    fn test_perf_event_unavailable_reads_zero() {
+        // The counters are per thread, so this only affects the test's own thread.
+        let kind = PerfEventKind::TaskClock;
+        set_unavailable(kind);
+        assert!(!is_available(kind));
+        assert_eq!(read_thread_counter(kind), 0);
+        assert_eq!(read_perf_event(kind), 0);
+    }
+
+    #[test]
+    fn test_perf_counter_user_read_matches_syscall() {
+        // Software events, such as the task clock, are never on a hardware counter.
+        let Some(counter) = [PerfEventKind::Instructions, PerfEventKind::Cycles]
+            .into_iter()
+            .find_map(|kind| PerfCounter::open(kind).ok())
+        else {
+            return;
+        };
+        // Virtual machines without rdpmc, hybrid CPUs with a PMU per core type, and events
+        // that are not scheduled yet are only read through the system call.
+        if !counter.has_user_read() {
+            return;
+        }
+
+        let fast = counter.read().unwrap();
+        let slow = counter.read_syscall().unwrap();
+        let fast_again = counter.read().unwrap();
+        assert!(
+            fast <= slow && slow <= fast_again,
+            "Perf counter should be monotonic across read paths"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::metrics::Instant;
+
+use super::{CallSite, ProfileBlock, Profiler};
+
+/// Where the anchor of an instrumented future is found.
+///
+/// Anchors assigned to call-sites are looked up on every poll, because each thread keeps
+/// its own call-site map and the future may be polled by a different thread each time.
+#[derive(Debug, Clone, Copy)]
+enum AnchorIndex {
+    Index(usize),
+    CallSite(CallSite),
+}
+
+impl AnchorIndex {
+    #[inline(always)]
+    fn resolve(self) -> usize {
+        match self {
+            Self::Index(index) => index,
+            Self::CallSite(callsite) => Profiler::get_or_insert(callsite),
+        }
+    }
+}
+
+/// A future that profiles the future it wraps.
+///
+/// Every poll of the inner future is timed by a profile block, opened and closed by the
+/// polling thread, so the block records the busy time of the future and every poll counts
+/// as a hit. The wall time, from the first poll until the future completes, is measured
+/// with the OS clock and recorded by the thread that completes the future.
+#[derive(Debug)]
+pub struct Instrumented<F> {
+    future: F,
+    label: &'static str,
+    anchor: AnchorIndex,
+    bytes: u64,
+    first_poll: Option<Instant>,
+}
+
+impl<F: Future> Instrumented<F> {
+    /// Profiles `future` with the anchor at `anchor_index`.
+    pub fn new(future: F, label: &'static str, anchor_index: usize) -> Self {
+        Self::with_anchor(future, label, AnchorIndex::Index(anchor_index))
+    }
+
+    /// Profiles `future` with the anchor assigned to `callsite` by the polling thread.
+    pub fn at_call_site(future: F, label: &'static str, callsite: CallSite) -> Self {
+        Self::with_anchor(future, label, AnchorIndex::CallSite(callsite))
+    }
+
+    fn with_anchor(future: F, label: &'static str, anchor: AnchorIndex) -> Self {
+        Self {
+            future,
+            label,
+            anchor,
+            bytes: 0,
+            first_poll: None,
+        }
+    }
+
+    /// Sets the number of bytes processed by the future, to report its throughput.
+    pub fn bytes(mut self, bytes: u64) -> Self {
+        self.bytes = bytes;
+        self
+    }
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is never moved out of `self`, and no other field is pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        let anchor_index = this.anchor.resolve();
+        let start = *this.first_poll.get_or_insert_with(Instant::now);
+        // The processed bytes are only added once, by the first poll.
+        let block =
+            ProfileBlock::with_bytes(this.label, anchor_index, std::mem::take(&mut this.bytes));
+        let poll = future.poll(cx);
+        drop(block);
+
+        if poll.is_ready() {
+            let wall = Instant::now().duration_since(start);
+            Profiler::record_wall_time(anchor_index, wall);
+        }
+        poll
+    }
+}
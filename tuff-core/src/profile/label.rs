@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+/// A label known only at runtime, that creates or reuses an anchor when a block is opened.
+///
+/// Blocks with runtime labels look their anchor up in a map of the thread's profiler, so
+/// they cost more to open than blocks with `&'static str` labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DynamicLabel<'a> {
+    /// A label built at runtime, such as a table name or a request type.
+    Name(&'a str),
+
+    /// A static label split by a key, such as a shard id, reported as `label[key]`.
+    Keyed(&'static str, u64),
+}
+
+impl<'a> From<&'a str> for DynamicLabel<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl<'a> From<&'a String> for DynamicLabel<'a> {
+    fn from(name: &'a String) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<(&'static str, u64)> for DynamicLabel<'_> {
+    fn from((label, key): (&'static str, u64)) -> Self {
+        Self::Keyed(label, key)
+    }
+}
+
+/// Returns a `'static` copy of the label, allocated once per distinct label.
+///
+/// Anchors keep their label for the lifetime of the program, so the interned labels are
+/// never freed. The profilers only intern a label when they create an anchor for it, so
+/// each thread interns at most as many labels as it has anchors.
+pub fn intern(label: &str) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match interned.get(label) {
+        Some(label) => label,
+        None => {
+            let label: &'static str = Box::leak(label.into());
+            interned.insert(label);
+            label
+        }
+    }
+}
@@ -1,8 +1,115 @@
 pub mod profiler;
-pub use profiler::{CallSite, ProfileBlock, Profiler};
+pub use profiler::{CallSite, ProfileBlock, Profiler, Token};
+
+pub mod overhead;
+pub use overhead::Overhead;
+
+pub mod future;
+pub use future::Instrumented;
+
+pub mod label;
+pub use label::DynamicLabel;
+
+/// Shortens the type name of an item nested in a function into a label for the function.
+///
+/// `std::any::type_name` of a nested item is the full path of the function followed by
+/// the item's name. The label keeps the function name, so `crate::module::free::__tuff_fn`
+/// becomes `free`, preceded by the type for trait methods, so
+/// `<crate::Type as crate::Trait>::method::__tuff_fn` becomes `Type::method`.
+///
+/// The path of an inherent method can not be told apart from the path of a function in a
+/// module, so use [`method_label`] for functions in an `impl` block.
+pub fn function_label(type_name: &'static str) -> &'static str {
+    label(type_name, false)
+}
+
+/// Like [`function_label`], for a function in an `impl` block, so that
+/// `crate::module::Type::method::__tuff_fn` becomes `Type::method`.
+pub fn method_label(type_name: &'static str) -> &'static str {
+    label(type_name, true)
+}
+
+fn label(type_name: &'static str, in_impl: bool) -> &'static str {
+    let Some((path, _)) = rsplit_path(type_name) else {
+        return type_name;
+    };
+    let Some((prefix, name)) = rsplit_path(path) else {
+        return path;
+    };
+    let parent = rsplit_path(prefix).map_or(prefix, |(_, parent)| parent);
+
+    // A trait method is in `<Type as Trait>`, and is labelled by the type.
+    if let Some(qualified) = parent.strip_prefix('<').and_then(|p| p.strip_suffix('>')) {
+        let self_ty = split_top_level(qualified, " as ").map_or(qualified, |(ty, _)| ty);
+        let self_ty = rsplit_path(self_ty).map_or(self_ty, |(_, ty)| ty);
+        return label::intern(&format!("{self_ty}::{name}"));
+    }
+    if in_impl {
+        &path[path.len() - parent.len() - 2 - name.len()..]
+    } else {
+        name
+    }
+}
+
+/// Splits a path at its last `::`, outside of generic arguments.
+fn rsplit_path(path: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in path.char_indices().rev() {
+        match c {
+            '>' => depth += 1,
+            '<' => depth -= 1,
+            ':' if depth == 0 && path[..i].ends_with(':') => {
+                return Some((&path[..i - 1], &path[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a type at the first `separator` outside of generic arguments.
+fn split_top_level<'a>(ty: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    let mut depth = 0;
+    for (i, c) in ty.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 && ty[i..].starts_with(separator) => {
+                return Some((&ty[..i], &ty[i + separator.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
 
 #[macro_export]
 macro_rules! profile_block {
+    // Specify the label, anchor index and processed bytes
+    ([$label:literal, $index:expr, bytes = $bytes:expr] $($body:tt)*) => {
+        ::paste::paste! {
+            let [<__profile_block _ $label _ $index>] =
+                $crate::ProfileBlock::with_bytes($label, $index, ($bytes) as u64);
+            $($body)*
+            drop([<__profile_block _ $label _ $index>]);
+        }
+    };
+
+    // Specify the label and processed bytes
+    ([$label:literal, bytes = $bytes:expr] $($body:tt)*) => {
+        let __idx = {
+            const __CALL_SITE: $crate::CallSite = $crate::CallSite::new(file!(), line!(), column!());
+            $crate::Profiler::get_or_insert(__CALL_SITE)
+        };
+
+        ::paste::paste! {
+            let [<__profile_block _ $label>] =
+                $crate::ProfileBlock::with_bytes($label, __idx, ($bytes) as u64);
+            $($body)*
+            drop([<__profile_block _ $label>]);
+        }
+    };
+
     // Specify the label and anchor index
     ([$label:literal, $index:expr] $($body:tt)*) => {
         ::paste::paste! {
@@ -26,3 +133,61 @@ macro_rules! profile_block {
         }
     }
 }
+
+/// Profiles an expression and evaluates to its value.
+///
+/// Unlike `profile_block!`, the block is an expression of its own, so the names defined in
+/// it do not leak into the enclosing scope. The block is closed when the expression has
+/// been evaluated, or when it returns early with `return` or `?`.
+///
+/// ```ignore
+/// let sum = profile!("sum", values.iter().sum::<u64>());
+/// let parsed = profile!("parse", index = 3, input.parse::<u32>()?);
+/// let copied = profile!("copy", bytes = input.len(), input.to_vec());
+/// let rows = profile!(dyn table.name(), table.scan());
+/// ```
+#[macro_export]
+macro_rules! profile {
+    // Specify the label, anchor index and processed bytes
+    ($label:literal, index = $index:expr, bytes = $bytes:expr, $body:expr $(,)?) => {{
+        let __block = $crate::ProfileBlock::with_bytes($label, $index, ($bytes) as u64);
+        $body
+    }};
+
+    // Specify the label and anchor index
+    ($label:literal, index = $index:expr, $body:expr $(,)?) => {{
+        let __block = $crate::ProfileBlock::new($label, $index);
+        $body
+    }};
+
+    // Specify the label and processed bytes
+    ($label:literal, bytes = $bytes:expr, $body:expr $(,)?) => {{
+        let __idx = {
+            const __CALL_SITE: $crate::CallSite =
+                $crate::CallSite::new(file!(), line!(), column!());
+            $crate::Profiler::get_or_insert(__CALL_SITE)
+        };
+        let __block = $crate::ProfileBlock::with_bytes($label, __idx, ($bytes) as u64);
+        $body
+    }};
+
+    // Specify a runtime label, such as a `&str` or a `(&'static str, u64)` pair
+    (dyn $label:expr, $body:expr $(,)?) => {{
+        let __block = $crate::ProfileBlock::dynamic($label, 0);
+        $body
+    }};
+
+    // Specify only the label
+    ($label:literal, $body:expr $(,)?) => {{
+        let __idx = {
+            const __CALL_SITE: $crate::CallSite =
+                $crate::CallSite::new(file!(), line!(), column!());
+            $crate::Profiler::get_or_insert(__CALL_SITE)
+        };
+        let __block = $crate::ProfileBlock::new($label, __idx);
+        $body
+    }};
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,47 @@
+/// Estimated cost of a profile block, in units of the primary metric.
+///
+/// Opening and closing a block has a cost that is partly recorded by the block itself,
+/// between its two reads of the metric, and partly by its parent block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Overhead {
+    /// Metric elapsed that an empty block records for itself.
+    pub per_block: u64,
+
+    /// Metric elapsed that opening and closing an empty block adds to its parent.
+    pub per_nested_block: u64,
+}
+
+impl Overhead {
+    /// Removes the overhead of a block's own hits, and of its direct children hits.
+    pub(crate) fn correct_exclusive(
+        &self,
+        exclusive: u64,
+        hit_count: u64,
+        child_hit_count: u64,
+    ) -> u64 {
+        let own = hit_count.saturating_mul(self.per_block);
+        let children =
+            child_hit_count.saturating_mul(self.per_nested_block.saturating_sub(self.per_block));
+        exclusive.saturating_sub(own).saturating_sub(children)
+    }
+
+    /// Removes the overhead of a block's own hits, and of every block nested inside it.
+    pub(crate) fn correct_inclusive(
+        &self,
+        inclusive: u64,
+        hit_count: u64,
+        nested_hit_count: u64,
+    ) -> u64 {
+        let own = hit_count.saturating_mul(self.per_block);
+        let nested = nested_hit_count.saturating_mul(self.per_nested_block);
+        inclusive.saturating_sub(own).saturating_sub(nested)
+    }
+
+    /// Removes the overhead of a single execution of a block, and of the blocks nested in it.
+    pub(crate) fn correct_single(&self, elapsed: u64, nested_hit_count: u64) -> u64 {
+        let nested = nested_hit_count.saturating_mul(self.per_nested_block);
+        elapsed
+            .saturating_sub(self.per_block)
+            .saturating_sub(nested)
+    }
+}
@@ -1,14 +1,41 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::marker::PhantomData;
 
-use crate::arch::read_cpu_counter;
-use crate::metrics::{Counter, Duration, MetricType, ProfileMetric};
-use crate::os::read_os_time;
-use crate::report::{Measurement, ProfileReport};
+use crate::arch::{CpuCounter, CpuCounterSerialized};
+use crate::error::{Error, Result};
+use crate::metrics::{
+    Duration, Frequency, MAX_EXTRA_METRICS, MetricType, PerfEventKind, ProfileMetric, TickUnit,
+    TimeSource,
+};
+use crate::os::{CoarseClock, OsClock};
+use crate::report::{
+    CallParent, ExtraMeasurement, Histogram, Measurement, OverheadEstimate, ProfileReport,
+};
+#[cfg(feature = "reporter")]
+use crate::reporter;
+
+use super::Overhead;
+use super::label::{self, DynamicLabel};
 
 const PROFILER_SIZE: usize = 1024;
 const ANCHOR_IDX_INIT: usize = 1;
 
+/// The last anchor records every block opened after all the others are in use.
+const OVERFLOW_ANCHOR_IDX: usize = PROFILER_SIZE - 1;
+const OVERFLOW_LABEL: &str = "<overflow>";
+
+/// The root anchor is the parent of the blocks opened outside any other block.
+const ROOT_ANCHOR_IDX: usize = 0;
+
+/// Parent of an anchor that was never hit.
+const NO_PARENT_IDX: usize = u32::MAX as usize;
+
+/// The root anchor is reused to time the calibration blocks, and restored afterwards.
+const CALIBRATION_ANCHOR_IDX: usize = ROOT_ANCHOR_IDX;
+const CALIBRATION_ROUNDS: usize = 16;
+const CALIBRATION_BLOCKS_PER_ROUND: u64 = 1024;
+
 thread_local! {
     static THREAD_PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
 }
@@ -29,51 +56,478 @@ impl CallSite {
 
 pub struct Profiler {
     current_open_block: usize,
-    anchors: [ProfileAnchor; PROFILER_SIZE],
+    anchors: Box<[ProfileAnchor; PROFILER_SIZE]>,
+    /// Measurements of the anchors that only some blocks update, by anchor.
+    details: Box<[AnchorDetails; PROFILER_SIZE]>,
     anchors_map: HashMap<CallSite, usize>,
     next_anchor_idx: usize,
 
+    /// Anchors of the blocks with runtime labels, by interned label and by label and key.
+    named_anchors_map: HashMap<&'static str, usize>,
+    keyed_anchors_map: HashMap<(&'static str, u64), (usize, &'static str)>,
+
+    /// Type of the primary metric, used to report its values.
     metric_type: MetricType,
-    metric_init: Option<u64>,
-    metric_final: Option<u64>,
+    /// Frequency of a time source of your own that counts cycles, reported with its values.
+    source_frequency: Option<Frequency>,
+    /// Event of the primary metric, when it is a performance counter.
+    perf_event_kind: PerfEventKind,
+    /// Opens and closes the blocks, for the source of the primary metric.
+    block_fns: BlockFns,
+
+    /// Whether the profiler was started at least once.
+    started: bool,
+    /// Value of the primary metric when the current profiling cycle started, while running.
+    cycle_start: Option<u64>,
+    /// Primary metric elapsed in the profiling cycles stopped since the last reset.
+    elapsed_stopped: u64,
+
+    /// Metrics recorded alongside `metric_type`; only the first `extra_metric_count` are used.
+    extra_metric_types: [MetricType; MAX_EXTRA_METRICS],
+    extra_metric_count: usize,
+
+    /// Values of the primary and extra metrics at the last reset.
+    reset_at: u64,
+    extra_reset: [u64; MAX_EXTRA_METRICS],
+
+    /// Number of profile blocks closed so far, used to count the blocks nested in another.
+    blocks_closed: u64,
+
+    /// Number of profile blocks opened so far, which numbers each open block.
+    blocks_opened: u64,
+    /// Number of the innermost open block, or zero outside any block.
+    current_open_serial: u64,
+    /// Blocks discarded by [`Profiler::end`] while blocks opened in them were still open,
+    /// with the anchor and number of their parent, which those blocks are moved to.
+    discarded_blocks: Vec<(u64, usize, u64)>,
+
+    /// Number of resets and discarded blocks, to recognise the blocks opened before them.
+    generation: u64,
+    /// Generation of the last reset, after which the blocks of earlier generations record
+    /// only the part of their execution after the reset.
+    reset_generation: u64,
+    overhead: Option<Overhead>,
+    subtract_overhead: bool,
+
+    /// Histogram of every anchor, if they are recorded; empty otherwise.
+    histograms: Vec<Histogram>,
+
+    /// Number of blocks opened with [`Profiler::begin`] and not yet ended.
+    open_tokens: usize,
+
+    /// Whether the overhead is being calibrated, when the measurements must not be published.
+    calibrating: bool,
+    /// Interval of the background reporter that the measurements were last published for.
+    #[cfg(feature = "reporter")]
+    reporter_epoch: u64,
 }
 
 impl Profiler {
     fn new() -> Self {
         Self {
             current_open_block: 0,
-            anchors: [ProfileAnchor::new("Uninit"); PROFILER_SIZE],
+            anchors: boxed_array(ProfileAnchor::new()),
+            details: boxed_array(AnchorDetails::new("Uninit")),
             anchors_map: HashMap::new(),
             next_anchor_idx: ANCHOR_IDX_INIT,
+            named_anchors_map: HashMap::new(),
+            keyed_anchors_map: HashMap::new(),
             metric_type: MetricType::OsClock,
-            metric_init: Some(0),
-            metric_final: None,
+            source_frequency: None,
+            perf_event_kind: PerfEventKind::TaskClock,
+            block_fns: BlockFns::of::<Source<OsClock>>(),
+            started: false,
+            cycle_start: None,
+            elapsed_stopped: 0,
+            extra_metric_types: [MetricType::OsClock; MAX_EXTRA_METRICS],
+            extra_metric_count: 0,
+            reset_at: 0,
+            extra_reset: [0; MAX_EXTRA_METRICS],
+            blocks_closed: 0,
+            blocks_opened: 0,
+            current_open_serial: 0,
+            discarded_blocks: Vec::new(),
+            generation: 0,
+            reset_generation: 0,
+            overhead: None,
+            subtract_overhead: false,
+            histograms: Vec::new(),
+            open_tokens: 0,
+            calibrating: false,
+            #[cfg(feature = "reporter")]
+            reporter_epoch: 0,
         }
     }
 
     pub fn start_global(metric_type: MetricType) {
+        Self::start_global_extra(metric_type, &[]);
+    }
+
+    /// Starts the profiler recording several metrics for every profile block.
+    ///
+    /// The first metric is the primary one; it is used for the proportions, minimum and
+    /// maximum in the report. The rest are recorded as exclusive and inclusive totals.
+    ///
+    /// Fails if no metric, or more than `MAX_EXTRA_METRICS + 1` metrics, are given.
+    pub fn start_global_multi(metric_types: &[MetricType]) -> Result<()> {
+        let Some((&metric_type, extra_metric_types)) = metric_types.split_first() else {
+            return Err(Error::NoMetric);
+        };
+        if extra_metric_types.len() > MAX_EXTRA_METRICS {
+            return Err(Error::TooManyMetrics {
+                max: MAX_EXTRA_METRICS + 1,
+            });
+        }
+        Self::start_global_extra(metric_type, extra_metric_types);
+        Ok(())
+    }
+
+    fn start_global_extra(metric_type: MetricType, extra_metric_types: &[MetricType]) {
+        THREAD_PROFILER.with(|p| {
+            let mut profiler = p.borrow_mut();
+            profiler.extra_metric_count = extra_metric_types.len();
+            profiler.extra_metric_types[..extra_metric_types.len()]
+                .copy_from_slice(extra_metric_types);
+            let block_fns = match metric_type {
+                MetricType::OsClock => BlockFns::of::<Source<OsClock>>(),
+                MetricType::CoarseClock => BlockFns::of::<Source<CoarseClock>>(),
+                MetricType::CpuCounter => BlockFns::of::<Source<CpuCounter>>(),
+                MetricType::CpuCounterSerialized => BlockFns::of::<Source<CpuCounterSerialized>>(),
+                MetricType::PerfEvent(kind) => {
+                    profiler.perf_event_kind = kind;
+                    BlockFns::of::<PerfEventReader>()
+                }
+            };
+            profiler.start(metric_type, None, block_fns);
+        });
+    }
+
+    /// Starts the profiler with a [`TimeSource`] as the primary metric.
+    ///
+    /// The source can be one of the clocks of `arch` and `os`, or one of your own, such as
+    /// a mock clock. Its values are reported in the unit of the source, and converted to
+    /// time with its own frequency.
+    pub fn start_global_with<S: TimeSource>() {
         THREAD_PROFILER.with(|p| {
             let mut profiler = p.borrow_mut();
-            profiler.metric_type = metric_type;
-            profiler.metric_init = Some(profiler.read_current_metric());
+            profiler.extra_metric_count = 0;
+            let (metric_type, frequency) = match (S::metric_type(), S::unit()) {
+                (Some(metric_type), _) => (metric_type, None),
+                (None, TickUnit::Nanoseconds) => (MetricType::OsClock, None),
+                (None, TickUnit::Cycles) => (MetricType::CpuCounter, Some(S::frequency())),
+            };
+            profiler.start(metric_type, frequency, BlockFns::of::<Source<S>>());
         });
     }
 
+    /// Starts a profiling cycle.
+    ///
+    /// The anchors keep accumulating over several start and stop cycles, until
+    /// [`Profiler::reset`] is called. Reset the profiler before starting it with a
+    /// different metric, so that the values of different metrics are not added up.
+    fn start(
+        &mut self,
+        metric_type: MetricType,
+        source_frequency: Option<Frequency>,
+        block_fns: BlockFns,
+    ) {
+        self.metric_type = metric_type;
+        self.source_frequency = source_frequency;
+        self.block_fns = block_fns;
+        // Open any lazily initialised counters before the profile starts.
+        self.read_extra_metrics();
+        self.started = true;
+        self.cycle_start = Some(self.read_current_metric());
+    }
+
+    /// Stops the current profiling cycle. Does nothing if the profiler is not running.
     pub fn stop_global() {
         THREAD_PROFILER.with(|p| {
             let mut profiler = p.borrow_mut();
-            profiler.metric_final = Some(profiler.read_current_metric());
+            debug_assert_eq!(
+                profiler.open_tokens, 0,
+                "Profiler stopped with blocks that were begun but never ended"
+            );
+            if let Some(cycle_start) = profiler.cycle_start.take() {
+                profiler.elapsed_stopped += profiler.read_current_metric() - cycle_start;
+            }
         });
     }
 
-    // TODO: Change this
-    #[inline(always)]
+    /// Clears the measurements of every block, and the metric elapsed so far.
+    ///
+    /// If the profiler is running, a new profiling cycle starts now. Blocks that are open
+    /// during the reset only record the part of their execution after it. The anchors
+    /// assigned to call-sites and runtime labels, and the calibrated overhead, are kept.
+    pub fn reset() {
+        THREAD_PROFILER.with(|p| p.borrow_mut().reset_measurements());
+    }
+
+    fn reset_measurements(&mut self) {
+        self.anchors.fill(ProfileAnchor::new());
+        for details in self.details.iter_mut() {
+            *details = AnchorDetails::new(details.label);
+        }
+        self.blocks_closed = 0;
+        self.generation += 1;
+        self.reset_generation = self.generation;
+        self.elapsed_stopped = 0;
+        self.histograms.fill(Histogram::new());
+        self.extra_reset = self.read_extra_metrics();
+        self.reset_at = self.read_current_metric();
+        if self.cycle_start.is_some() {
+            self.cycle_start = Some(self.reset_at);
+        }
+    }
+
+    /// Publishes a copy of the measurements to the background reporter, once per interval.
+    ///
+    /// The reporter thread builds the report, relative to the last publication, so that
+    /// the block that publishes only pays for the copy.
+    #[cfg(feature = "reporter")]
+    fn publish_for_reporter(&mut self) {
+        let epoch = reporter::epoch();
+        if epoch == self.reporter_epoch || self.calibrating {
+            return;
+        }
+        self.reporter_epoch = epoch;
+        if self.started && reporter::is_running() {
+            self.publish_snapshot();
+        }
+    }
+
+    #[cfg(feature = "reporter")]
+    #[cold]
+    #[inline(never)]
+    fn publish_snapshot(&self) {
+        // The anchors that were never hit are not reported, so they are not copied.
+        let anchors = self
+            .anchors
+            .iter()
+            .enumerate()
+            .filter(|(_, anchor)| anchor.hit_count > 0)
+            .map(|(index, anchor)| AnchorSnapshot {
+                index,
+                anchor: *anchor,
+                details: self.details[index],
+                histogram: self.histograms.get(index).copied(),
+            })
+            .collect();
+        reporter::publish(ProfilerSnapshot {
+            thread: std::thread::current(),
+            reset_generation: self.reset_generation,
+            settings: self.report_settings(),
+            anchors,
+            histograms_recorded: !self.histograms.is_empty(),
+            elapsed: self.elapsed_running(),
+        });
+    }
+
+    /// Publishes the measurements of the calling thread since its last publication.
+    #[cfg(feature = "reporter")]
+    pub(crate) fn publish_global() {
+        THREAD_PROFILER.with(|p| {
+            let profiler = p.borrow();
+            if profiler.started && !profiler.calibrating {
+                profiler.publish_snapshot();
+            }
+        });
+    }
+
+    /// Opens a profile block that is closed explicitly by [`Profiler::end`].
+    ///
+    /// Unlike [`ProfileBlock`], the block is not bound to a scope, so it can be opened and
+    /// closed in different functions, as long as it is on the same thread and blocks are
+    /// still closed in the reverse order they were opened.
+    pub fn begin(label: &'static str, anchor_index: usize) -> Token {
+        let block = ProfileBlock::new(label, anchor_index);
+        THREAD_PROFILER.with(|p| p.borrow_mut().open_tokens += 1);
+        Token {
+            label,
+            block: Some(block),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Closes a profile block opened by [`Profiler::begin`].
+    ///
+    /// Fails if the block is not the innermost open block, including an outer recursive
+    /// block of the same anchor. The block is then discarded without being recorded, and
+    /// the blocks still open in it are recorded as opened in its parent.
+    pub fn end(mut token: Token) -> Result<()> {
+        // Only `end` and `drop` take the block, and both consume the token.
+        let Some(block) = token.block.take() else {
+            return Ok(());
+        };
+        let result = THREAD_PROFILER.with(|p| {
+            let mut profiler = p.borrow_mut();
+            profiler.open_tokens -= 1;
+            if profiler.current_open_serial == block.serial {
+                return Ok(());
+            }
+            profiler
+                .discarded_blocks
+                .push((block.serial, block.parent_index, block.parent_serial));
+            // The blocks open in the discarded block are moved to its parent when closed.
+            profiler.generation += 1;
+            Err(Error::MismatchedToken {
+                ended: token.label,
+                innermost: profiler.details[profiler.current_open_block].label,
+            })
+        });
+        match result {
+            Ok(()) => drop(block),
+            Err(_) => block.discard(),
+        }
+        result
+    }
+
+    /// Estimates the overhead of a profile block for the active primary metric.
+    ///
+    /// Times batches of empty profile blocks, and keeps the lowest estimate over several
+    /// rounds to filter out interrupts and context switches. The estimate is stored and
+    /// reported by [`Profiler::report`]. It should be called after
+    /// [`Profiler::start_global`], outside of any profile block.
+    ///
+    /// Panics if it is called inside a profile block.
+    pub fn calibrate_overhead() -> Overhead {
+        let (saved_anchor, saved_details, saved_blocks_closed) = THREAD_PROFILER.with(|p| {
+            let mut profiler = p.borrow_mut();
+            assert_eq!(
+                profiler.current_open_block, 0,
+                "Overhead calibration inside a profile block"
+            );
+            profiler.calibrating = true;
+            (
+                profiler.anchors[CALIBRATION_ANCHOR_IDX],
+                profiler.details[CALIBRATION_ANCHOR_IDX],
+                profiler.blocks_closed,
+            )
+        });
+
+        let mut overhead = Overhead {
+            per_block: u64::MAX,
+            per_nested_block: u64::MAX,
+        };
+        let calibration_start = THREAD_PROFILER.with(|p| p.borrow().read_current_metric());
+        for _ in 0..CALIBRATION_ROUNDS {
+            let start = THREAD_PROFILER.with(|p| {
+                let mut profiler = p.borrow_mut();
+                profiler.anchors[CALIBRATION_ANCHOR_IDX] = ProfileAnchor::new();
+                profiler.read_current_metric()
+            });
+            for _ in 0..CALIBRATION_BLOCKS_PER_ROUND {
+                drop(std::hint::black_box(ProfileBlock::new(
+                    "Calibration",
+                    CALIBRATION_ANCHOR_IDX,
+                )));
+            }
+            THREAD_PROFILER.with(|p| {
+                let profiler = p.borrow();
+                let end = profiler.read_current_metric();
+                let recorded = profiler.anchors[CALIBRATION_ANCHOR_IDX].elapsed_inclusive;
+                overhead.per_block =
+                    std::cmp::min(overhead.per_block, recorded / CALIBRATION_BLOCKS_PER_ROUND);
+                overhead.per_nested_block = std::cmp::min(
+                    overhead.per_nested_block,
+                    (end - start) / CALIBRATION_BLOCKS_PER_ROUND,
+                );
+            });
+        }
+
+        THREAD_PROFILER.with(|p| {
+            let mut profiler = p.borrow_mut();
+            profiler.anchors[CALIBRATION_ANCHOR_IDX] = saved_anchor;
+            profiler.details[CALIBRATION_ANCHOR_IDX] = saved_details;
+            profiler.blocks_closed = saved_blocks_closed;
+            profiler.calibrating = false;
+            profiler.overhead = Some(overhead);
+            // Leave the calibration out of the total metric elapsed.
+            let calibration_elapsed = profiler.read_current_metric() - calibration_start;
+            profiler.cycle_start = profiler
+                .cycle_start
+                .map(|start| start + calibration_elapsed);
+        });
+        overhead
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_overhead(overhead: Overhead) {
+        THREAD_PROFILER.with(|p| p.borrow_mut().overhead = Some(overhead));
+    }
+
+    /// Sets whether the calibrated overhead is subtracted from the reported metrics.
+    ///
+    /// Has no effect until [`Profiler::calibrate_overhead`] is called.
+    pub fn subtract_overhead(subtract: bool) {
+        THREAD_PROFILER.with(|p| p.borrow_mut().subtract_overhead = subtract);
+    }
+
+    /// Moves a block opened in a discarded block to the parent of the discarded block.
+    fn reparent_discarded(&mut self, parent_index: &mut usize, parent_serial: &mut u64) {
+        while let Some(position) = self
+            .discarded_blocks
+            .iter()
+            .position(|(serial, ..)| serial == parent_serial)
+        {
+            let (_, index, serial) = self.discarded_blocks.swap_remove(position);
+            *parent_index = index;
+            *parent_serial = serial;
+        }
+    }
+
+    /// Sets whether the distribution of the primary metric per hit is recorded, as a
+    /// [`Histogram`] for every block.
+    ///
+    /// The histograms cost a bucket update per block, and are not corrected for the
+    /// overhead. Stopping recording them discards the histograms recorded so far.
+    pub fn record_histograms(record: bool) {
+        THREAD_PROFILER.with(|p| {
+            let mut profiler = p.borrow_mut();
+            if !record {
+                profiler.histograms = Vec::new();
+            } else if profiler.histograms.is_empty() {
+                profiler.histograms = vec![Histogram::new(); PROFILER_SIZE];
+            }
+        });
+    }
+
+    /// Reads the primary metric, outside of the blocks.
     fn read_current_metric(&self) -> u64 {
-        match self.metric_type {
-            MetricType::OsClock => read_os_time(),
-            MetricType::CpuCounter => read_cpu_counter(),
-            MetricType::CpuCounterSerialized => todo!(),
+        (self.block_fns.read)(self)
+    }
+
+    fn report_settings(&self) -> ReportSettings {
+        ReportSettings {
+            metric_type: self.metric_type,
+            source_frequency: self.source_frequency,
+            extra_metric_types: self.extra_metric_types,
+            extra_metric_count: self.extra_metric_count,
+            overhead: self.overhead,
+            subtract_overhead: self.subtract_overhead,
+        }
+    }
+
+    #[inline(always)]
+    fn read_extra_metrics(&self) -> [u64; MAX_EXTRA_METRICS] {
+        let mut values = [0; MAX_EXTRA_METRICS];
+        for (value, metric_type) in values
+            .iter_mut()
+            .zip(&self.extra_metric_types[..self.extra_metric_count])
+        {
+            *value = metric_type.read();
         }
+        values
+    }
+
+    /// Records the wall time of a completed instrumented future.
+    pub(crate) fn record_wall_time(anchor_index: usize, wall: Duration) {
+        THREAD_PROFILER.with(|p| {
+            let mut profiler = p.borrow_mut();
+            let details = &mut profiler.details[anchor_index];
+            details.elapsed_wall += wall.as_nanos();
+            details.completion_count += 1;
+        });
     }
 
     #[inline(always)]
@@ -83,9 +537,7 @@ impl Profiler {
             match profiler.anchors_map.get(&callsite) {
                 Some(idx) => *idx,
                 None => {
-                    let idx = profiler.next_anchor_idx;
-                    profiler.next_anchor_idx += 1;
-
+                    let idx = profiler.next_anchor();
                     profiler.anchors_map.insert(callsite, idx);
 
                     idx
@@ -94,49 +546,338 @@ impl Profiler {
         })
     }
 
-    pub fn report() -> ProfileReport {
+    /// Returns a new anchor, for a call-site that is not a [`CallSite`].
+    #[cfg(feature = "tracing")]
+    pub(crate) fn new_anchor() -> usize {
+        THREAD_PROFILER.with(|p| p.borrow_mut().next_anchor())
+    }
+
+    /// Returns the anchor of a runtime label, and the label to record, creating them if needed.
+    ///
+    /// Once every anchor is in use, new labels share the `<overflow>` anchor, and are neither
+    /// interned nor remembered.
+    pub fn get_or_insert_dynamic(label: DynamicLabel<'_>) -> (usize, &'static str) {
         THREAD_PROFILER.with(|p| {
-            let profiler = p.borrow();
-            let into_metric = |value: u64| match profiler.metric_type {
-                MetricType::OsClock => ProfileMetric::OsClock(Duration::from_nanos(value)),
-                MetricType::CpuCounter | MetricType::CpuCounterSerialized => {
-                    ProfileMetric::CpuCounter(Counter::from_cycles(value))
+            let mut profiler = p.borrow_mut();
+            let known = match label {
+                DynamicLabel::Name(name) => profiler.named_anchors_map.contains_key(name),
+                DynamicLabel::Keyed(name, key) => {
+                    profiler.keyed_anchors_map.contains_key(&(name, key))
                 }
             };
+            if !known && profiler.next_anchor_idx == OVERFLOW_ANCHOR_IDX {
+                return (OVERFLOW_ANCHOR_IDX, OVERFLOW_LABEL);
+            }
+            match label {
+                DynamicLabel::Name(name) => match profiler.named_anchors_map.get_key_value(name) {
+                    Some((label, idx)) => (*idx, *label),
+                    None => {
+                        let label = label::intern(name);
+                        let idx = profiler.next_anchor();
+                        profiler.named_anchors_map.insert(label, idx);
+                        (idx, label)
+                    }
+                },
+                DynamicLabel::Keyed(name, key) => {
+                    match profiler.keyed_anchors_map.get(&(name, key)) {
+                        Some(anchor) => *anchor,
+                        None => {
+                            let label = label::intern(&format!("{name}[{key}]"));
+                            let idx = profiler.next_anchor();
+                            profiler.keyed_anchors_map.insert((name, key), (idx, label));
+                            (idx, label)
+                        }
+                    }
+                }
+            }
+        })
+    }
 
-            let metric_init_value = profiler.metric_init.expect("Profiler not started");
-            let metric_final_value = profiler.metric_final.expect("Profiler not finished");
+    /// Returns a new anchor, or the overflow anchor once all the others are in use.
+    fn next_anchor(&mut self) -> usize {
+        let idx = self.next_anchor_idx;
+        if idx == OVERFLOW_ANCHOR_IDX {
+            return idx;
+        }
+        self.next_anchor_idx += 1;
+        idx
+    }
 
-            let metric_init = into_metric(metric_init_value);
-            let metric_final = into_metric(metric_final_value);
+    /// Reports the measurements of the profiling cycles since the last reset.
+    ///
+    /// Fails if the profiler is still running; use [`Profiler::snapshot`] instead.
+    pub fn report() -> Result<ProfileReport> {
+        THREAD_PROFILER.with(|p| {
+            let profiler = p.borrow();
+            if !profiler.started {
+                return Err(Error::NotStarted);
+            }
+            if profiler.cycle_start.is_some() {
+                return Err(Error::NotStopped);
+            }
+            Ok(profiler.report_settings().build_report(
+                &*profiler.anchors,
+                &*profiler.details,
+                &profiler.histograms,
+                profiler.elapsed_stopped,
+            ))
+        })
+    }
 
-            let mut report = ProfileReport::new(metric_init, metric_final);
-            for anchor in profiler.anchors.iter() {
-                if anchor.hit_count == 0 {
-                    continue;
-                }
-                let stat = Measurement {
-                    label: anchor.label,
-                    hit_count: anchor.hit_count,
-                    elapsed_exclusive: into_metric(anchor.elapsed_exclusive as u64),
-                    elapsed_inclusive: into_metric(anchor.elapsed_inclusive),
-                    elapsed_min: into_metric(anchor.elapsed_min),
-                    elapsed_max: into_metric(anchor.elapsed_max),
-                };
-                report.push_measurement(stat);
-            }
-
-            report
+    /// Reports the measurements so far, without stopping the profiler.
+    ///
+    /// Blocks that are still open are not included, since they are recorded when closed.
+    pub fn snapshot() -> Result<ProfileReport> {
+        THREAD_PROFILER.with(|p| {
+            let profiler = p.borrow();
+            if !profiler.started {
+                return Err(Error::NotStarted);
+            }
+            Ok(profiler.snapshot_report())
         })
     }
+
+    fn snapshot_report(&self) -> ProfileReport {
+        self.report_settings().build_report(
+            &*self.anchors,
+            &*self.details,
+            &self.histograms,
+            self.elapsed_running(),
+        )
+    }
+
+    fn elapsed_running(&self) -> u64 {
+        let running = self
+            .cycle_start
+            .map_or(0, |start| self.read_current_metric() - start);
+        self.elapsed_stopped + running
+    }
 }
 
+/// Settings of a profiler that its measurements are reported with.
+#[derive(Clone, Copy)]
+struct ReportSettings {
+    metric_type: MetricType,
+    /// Frequency of a custom cycle source, which its ticks are converted with.
+    source_frequency: Option<Frequency>,
+    extra_metric_types: [MetricType; MAX_EXTRA_METRICS],
+    extra_metric_count: usize,
+    overhead: Option<Overhead>,
+    subtract_overhead: bool,
+}
+
+impl ReportSettings {
+    /// Wraps a value of the primary metric, in the unit of its source.
+    fn to_metric(self, value: u64) -> ProfileMetric {
+        match self.source_frequency {
+            Some(frequency) => ProfileMetric::Ticks(value, frequency),
+            None => self.metric_type.to_metric(value),
+        }
+    }
+
+    fn build_report(
+        &self,
+        anchors: &[ProfileAnchor],
+        details: &[AnchorDetails],
+        histograms: &[Histogram],
+        total_elapsed: u64,
+    ) -> ProfileReport {
+        let into_metric = |value: u64| self.to_metric(value);
+
+        let metric_init = into_metric(0);
+        let metric_final = into_metric(total_elapsed);
+
+        let mut report = ProfileReport::new(metric_init, metric_final);
+        let subtracted_overhead = self.overhead.filter(|_| self.subtract_overhead);
+        let mut total_hit_count = 0;
+        for (anchor_index, (anchor, details)) in anchors.iter().zip(details).enumerate() {
+            if anchor.hit_count == 0 {
+                continue;
+            }
+            total_hit_count += anchor.hit_count;
+
+            let mut elapsed_exclusive = anchor.elapsed_exclusive as u64;
+            let mut elapsed_inclusive = anchor.elapsed_inclusive;
+            let mut elapsed_min = anchor.elapsed_min;
+            let mut elapsed_max = anchor.elapsed_max;
+            if let Some(overhead) = subtracted_overhead {
+                elapsed_exclusive = overhead.correct_exclusive(
+                    elapsed_exclusive,
+                    anchor.hit_count,
+                    anchor.child_hit_count,
+                );
+                elapsed_inclusive = overhead.correct_inclusive(
+                    elapsed_inclusive,
+                    anchor.hit_count,
+                    anchor.nested_hit_count,
+                );
+                elapsed_min =
+                    overhead.correct_single(elapsed_min, details.elapsed_min_nested_hit_count);
+                elapsed_max =
+                    overhead.correct_single(elapsed_max, details.elapsed_max_nested_hit_count);
+                // The extreme hits are corrected for their own nested blocks, so a short
+                // hit with no nested blocks can end up above a long one with many.
+                elapsed_min = elapsed_min.min(elapsed_max);
+            }
+
+            let mut extra = [None; MAX_EXTRA_METRICS];
+            for (i, metric_type) in self.extra_metric_types[..self.extra_metric_count]
+                .iter()
+                .enumerate()
+            {
+                extra[i] = Some(ExtraMeasurement {
+                    elapsed_exclusive: metric_type.to_metric(details.extra_exclusive[i] as u64),
+                    elapsed_inclusive: metric_type.to_metric(details.extra_inclusive[i]),
+                });
+            }
+            let parent = match anchor.parent_index as usize {
+                _ if anchor.several_parents => CallParent::Several,
+                NO_PARENT_IDX | ROOT_ANCHOR_IDX => CallParent::Root,
+                parent => CallParent::Anchor(parent),
+            };
+            let stat = Measurement {
+                label: details.label,
+                anchor_index,
+                parent: Some(parent),
+                hit_count: anchor.hit_count,
+                elapsed_exclusive: into_metric(elapsed_exclusive),
+                elapsed_inclusive: into_metric(elapsed_inclusive),
+                elapsed_min: into_metric(elapsed_min),
+                elapsed_max: into_metric(elapsed_max),
+                processed_bytes: details.processed_bytes,
+                extra,
+                elapsed_wall: (details.completion_count > 0)
+                    .then(|| Duration::from_nanos(details.elapsed_wall)),
+                completion_count: details.completion_count,
+                histogram: histograms.get(anchor_index).copied(),
+            };
+            report.push_measurement(stat);
+        }
+
+        if let Some(overhead) = self.overhead {
+            report.set_overhead(OverheadEstimate {
+                per_block: into_metric(overhead.per_block),
+                per_nested_block: into_metric(overhead.per_nested_block),
+                instrumentation_cost: into_metric(
+                    total_hit_count.saturating_mul(overhead.per_nested_block),
+                ),
+                subtracted: self.subtract_overhead,
+            });
+        }
+
+        report
+    }
+}
+
+/// Copy of the measurements of a thread, which the reporter thread builds the report of an
+/// interval from.
+#[cfg(feature = "reporter")]
+pub(crate) struct ProfilerSnapshot {
+    thread: std::thread::Thread,
+    /// Generation of the last reset of the profiler, which clears the measurements.
+    reset_generation: u64,
+    settings: ReportSettings,
+    /// Anchors that were hit.
+    anchors: Vec<AnchorSnapshot>,
+    histograms_recorded: bool,
+    elapsed: u64,
+}
+
+#[cfg(feature = "reporter")]
+struct AnchorSnapshot {
+    index: usize,
+    anchor: ProfileAnchor,
+    details: AnchorDetails,
+    histogram: Option<Histogram>,
+}
+
+/// Measurements of a thread when it was last reported, which its next interval is reported
+/// relative to. Empty until the first report.
+#[cfg(feature = "reporter")]
+#[derive(Default)]
+pub(crate) struct IntervalBaseline {
+    reset_generation: u64,
+    anchors: Vec<ProfileAnchor>,
+    details: Vec<AnchorDetails>,
+    histograms: Vec<Histogram>,
+    elapsed: u64,
+}
+
+#[cfg(feature = "reporter")]
+impl ProfilerSnapshot {
+    pub(crate) fn thread_id(&self) -> std::thread::ThreadId {
+        self.thread.id()
+    }
+
+    /// Reports the measurements since `baseline`, and moves the baseline of the blocks it
+    /// reports.
+    ///
+    /// A block is reported in the interval it is closed in. The anchors of the blocks that
+    /// were not closed keep their baseline, so that the time of their children is taken off
+    /// them in the interval they are closed in.
+    pub(crate) fn interval_report(&self, baseline: &mut IntervalBaseline) -> ProfileReport {
+        // A reset clears the measurements, so the interval reports everything since then.
+        if baseline.anchors.is_empty() || baseline.reset_generation != self.reset_generation {
+            *baseline = IntervalBaseline {
+                reset_generation: self.reset_generation,
+                anchors: vec![ProfileAnchor::new(); PROFILER_SIZE],
+                details: vec![AnchorDetails::new(""); PROFILER_SIZE],
+                histograms: Vec::new(),
+                elapsed: 0,
+            };
+        }
+        // Histograms that stopped being recorded start over when they are recorded again.
+        match (self.histograms_recorded, baseline.histograms.is_empty()) {
+            (true, true) => baseline.histograms = vec![Histogram::new(); PROFILER_SIZE],
+            (false, false) => baseline.histograms = Vec::new(),
+            _ => {}
+        }
+
+        let mut deltas = vec![ProfileAnchor::new(); PROFILER_SIZE];
+        let mut details_deltas = vec![AnchorDetails::new(""); PROFILER_SIZE];
+        let mut histogram_deltas = vec![Histogram::new(); baseline.histograms.len()];
+        for snapshot in &self.anchors {
+            let index = snapshot.index;
+            let delta = snapshot.anchor.since(&baseline.anchors[index]);
+            if delta.hit_count == 0 {
+                continue;
+            }
+            deltas[index] = delta;
+            details_deltas[index] = snapshot.details.since(&baseline.details[index]);
+            baseline.anchors[index] = snapshot.anchor;
+            baseline.details[index] = snapshot.details;
+            if let Some(histogram) = snapshot.histogram {
+                histogram_deltas[index] = histogram.since(&baseline.histograms[index]);
+                baseline.histograms[index] = histogram;
+            }
+        }
+        let mut report = self.settings.build_report(
+            &deltas,
+            &details_deltas,
+            &histogram_deltas,
+            self.elapsed - baseline.elapsed,
+        );
+        report.set_thread(&self.thread);
+        baseline.elapsed = self.elapsed;
+        report
+    }
+}
+
+/// Returns an array on the heap, without building it on the stack first.
+fn boxed_array<T: Copy>(value: T) -> Box<[T; PROFILER_SIZE]> {
+    vec![value; PROFILER_SIZE]
+        .into_boxed_slice()
+        .try_into()
+        .unwrap_or_else(|_| unreachable!())
+}
+
+/// Measurements of an anchor that every block updates, which fit in one cache line.
+///
+/// The rest are in [`AnchorDetails`], so that a block only touches its own anchor's line
+/// and its parent's.
 #[repr(align(64))]
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct ProfileAnchor {
-    /// Label to identify the profile block.
-    label: &'static str,
-
     /// Number of times the anchor was hit.
     hit_count: u64,
 
@@ -151,50 +892,206 @@ pub(crate) struct ProfileAnchor {
 
     /// Maximum elapsed metric for single execution.
     elapsed_max: u64,
+
+    /// Number of times a direct child block was closed.
+    child_hit_count: u64,
+
+    /// Number of blocks closed while this one was open, not counting recursive hits twice.
+    nested_hit_count: u64,
+
+    /// Anchor of the block the first hit was opened in, not counting recursive hits, or
+    /// `NO_PARENT_IDX` before the first hit.
+    parent_index: u32,
+
+    /// Whether hits were opened in different parent blocks.
+    several_parents: bool,
 }
 
+const _: () = assert!(std::mem::size_of::<ProfileAnchor>() == 64);
+
 impl ProfileAnchor {
-    const fn new(label: &'static str) -> Self {
+    const fn new() -> Self {
         Self {
-            label,
             hit_count: 0,
             elapsed_exclusive: 0,
             elapsed_inclusive: 0,
             elapsed_min: u64::MAX,
             elapsed_max: 0,
+            child_hit_count: 0,
+            nested_hit_count: 0,
+            parent_index: NO_PARENT_IDX as u32,
+            several_parents: false,
+        }
+    }
+
+    /// Returns the measurements since `baseline`, an earlier copy of the anchor.
+    ///
+    /// The minimum and maximum can not be taken apart, so they are those since the reset.
+    #[cfg(feature = "reporter")]
+    fn since(&self, baseline: &ProfileAnchor) -> Self {
+        Self {
+            hit_count: self.hit_count - baseline.hit_count,
+            elapsed_exclusive: self.elapsed_exclusive - baseline.elapsed_exclusive,
+            elapsed_inclusive: self.elapsed_inclusive - baseline.elapsed_inclusive,
+            child_hit_count: self.child_hit_count - baseline.child_hit_count,
+            nested_hit_count: self.nested_hit_count - baseline.nested_hit_count,
+            ..*self
         }
     }
 }
 
+/// Label of an anchor, and the measurements that only the blocks using optional features
+/// update.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AnchorDetails {
+    /// Label to identify the profile block.
+    label: &'static str,
+
+    /// Number of bytes processed over all executions.
+    processed_bytes: u64,
+
+    /// Number of blocks nested in the execution with the minimum elapsed metric.
+    elapsed_min_nested_hit_count: u64,
+
+    /// Number of blocks nested in the execution with the maximum elapsed metric.
+    elapsed_max_nested_hit_count: u64,
+
+    /// Extra metrics elapsed not including children blocks.
+    extra_exclusive: [i64; MAX_EXTRA_METRICS],
+
+    /// Extra metrics elapsed including children blocks.
+    extra_inclusive: [u64; MAX_EXTRA_METRICS],
+
+    /// Wall time of the completed instrumented futures, in nanoseconds.
+    elapsed_wall: u64,
+
+    /// Number of instrumented futures that completed.
+    completion_count: u64,
+}
+
+impl AnchorDetails {
+    const fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            processed_bytes: 0,
+            elapsed_min_nested_hit_count: 0,
+            elapsed_max_nested_hit_count: 0,
+            extra_exclusive: [0; MAX_EXTRA_METRICS],
+            extra_inclusive: [0; MAX_EXTRA_METRICS],
+            elapsed_wall: 0,
+            completion_count: 0,
+        }
+    }
+
+    /// Returns the measurements since `baseline`, an earlier copy of the details.
+    #[cfg(feature = "reporter")]
+    fn since(&self, baseline: &AnchorDetails) -> Self {
+        Self {
+            processed_bytes: self.processed_bytes - baseline.processed_bytes,
+            extra_exclusive: std::array::from_fn(|i| {
+                self.extra_exclusive[i] - baseline.extra_exclusive[i]
+            }),
+            extra_inclusive: std::array::from_fn(|i| {
+                self.extra_inclusive[i] - baseline.extra_inclusive[i]
+            }),
+            elapsed_wall: self.elapsed_wall - baseline.elapsed_wall,
+            completion_count: self.completion_count - baseline.completion_count,
+            ..*self
+        }
+    }
+}
+
+/// A profile block opened by [`Profiler::begin`], to pass to [`Profiler::end`].
+///
+/// The profiler is local to each thread, so a token can not be sent to another thread.
+#[derive(Debug)]
+#[must_use = "the block stays open until the token is passed to `Profiler::end`"]
+pub struct Token {
+    label: &'static str,
+    block: Option<ProfileBlock>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Token {
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+}
+
+impl Drop for Token {
+    fn drop(&mut self) {
+        let Some(block) = self.block.take() else {
+            return;
+        };
+        // In release builds, a dropped token closes its block like a `ProfileBlock`.
+        THREAD_PROFILER.with(|p| p.borrow_mut().open_tokens -= 1);
+        drop(block);
+        debug_assert!(
+            std::thread::panicking(),
+            "Token of block `{}` dropped without `Profiler::end`",
+            self.label
+        );
+    }
+}
+
 #[derive(Debug)]
 pub struct ProfileBlock {
     anchor_index: usize,
     parent_index: usize,
     start_counter: u64,
     elapsed_inclusive_prev: u64,
+    extra_start: [u64; MAX_EXTRA_METRICS],
+    extra_inclusive_prev: [u64; MAX_EXTRA_METRICS],
+    blocks_closed_start: u64,
+    nested_hit_count_prev: u64,
+    generation: u64,
+
+    /// Number of the block, and of the block it was opened in, to check the nesting.
+    serial: u64,
+    parent_serial: u64,
+
+    /// Span entered while the block is open, if the blocks emit `tracing` spans.
+    #[cfg(feature = "tracing")]
+    _span: Option<crate::tracing::BlockSpan>,
 }
 
 impl ProfileBlock {
     pub fn new(label: &'static str, anchor_index: usize) -> Self {
-        THREAD_PROFILER.with(|p| {
+        Self::with_bytes(label, anchor_index, 0)
+    }
+
+    /// Opens a profile block with a label known only at runtime.
+    ///
+    /// The anchor of the label is created the first time the label is seen by the thread.
+    pub fn dynamic<'a>(label: impl Into<DynamicLabel<'a>>, bytes: u64) -> Self {
+        let (anchor_index, label) = Profiler::get_or_insert_dynamic(label.into());
+        Self::with_bytes(label, anchor_index, bytes)
+    }
+
+    /// Drops the block without recording it.
+    fn discard(self) {
+        let block = std::mem::ManuallyDrop::new(self);
+        #[cfg(feature = "tracing")]
+        drop(unsafe { std::ptr::read(&block._span) });
+        let _ = block;
+    }
+
+    /// Opens a profile block that processes `bytes` bytes, to report its throughput.
+    pub fn with_bytes(label: &'static str, anchor_index: usize, bytes: u64) -> Self {
+        // Entered before the block opens, so that the span is exited after it closes.
+        #[cfg(feature = "tracing")]
+        let span = crate::tracing::BlockSpan::enter(label);
+        #[allow(unused_mut)]
+        let mut block = THREAD_PROFILER.with(|p| {
             let mut profiler = p.borrow_mut();
-            let parent_index = profiler.current_open_block;
-            profiler.current_open_block = anchor_index;
-            {
-                let anchor = &mut profiler.anchors[anchor_index];
-                if anchor.hit_count == 0 {
-                    anchor.label = label;
-                }
-            }
-            let start_counter = profiler.read_current_metric();
-            let elapsed_inclusive_prev = profiler.anchors[anchor_index].elapsed_inclusive;
-            Self {
-                anchor_index,
-                parent_index,
-                start_counter,
-                elapsed_inclusive_prev,
-            }
-        })
+            let open = profiler.block_fns.open;
+            open(&mut profiler, label, anchor_index, bytes)
+        });
+        #[cfg(feature = "tracing")]
+        {
+            block._span = span;
+        }
+        block
     }
 }
 
@@ -202,19 +1099,229 @@ impl Drop for ProfileBlock {
     fn drop(&mut self) {
         THREAD_PROFILER.with(|p| {
             let mut profiler = p.borrow_mut();
-            let elapsed = profiler.read_current_metric() - self.start_counter;
-
-            let anchor = &mut profiler.anchors[self.anchor_index];
-            anchor.hit_count += 1;
-            anchor.elapsed_exclusive += elapsed as i64;
-            anchor.elapsed_inclusive = self.elapsed_inclusive_prev + elapsed;
-            anchor.elapsed_min = std::cmp::min(anchor.elapsed_min, elapsed);
-            anchor.elapsed_max = std::cmp::max(anchor.elapsed_max, elapsed);
-
-            // Account for nested calls
-            profiler.current_open_block = self.parent_index;
-            let parent = &mut profiler.anchors[self.parent_index];
-            parent.elapsed_exclusive -= elapsed as i64;
+            let close = profiler.block_fns.close;
+            close(&mut profiler, self);
         });
     }
 }
+
+/// Functions that open and close the profile blocks, and read the primary metric.
+///
+/// They are instantiated for the source of the primary metric when the profiler starts, so
+/// that the source is read inline, without a branch on the kind of metric per read.
+#[derive(Clone, Copy)]
+struct BlockFns {
+    open: fn(&mut Profiler, &'static str, usize, u64) -> ProfileBlock,
+    close: fn(&mut Profiler, &mut ProfileBlock),
+    read: fn(&Profiler) -> u64,
+}
+
+impl BlockFns {
+    fn of<R: MetricReader>() -> Self {
+        Self {
+            open: Profiler::open_block::<R>,
+            close: Profiler::close_block::<R>,
+            read: R::read,
+        }
+    }
+}
+
+/// Reads the primary metric of a profiler.
+trait MetricReader {
+    fn read(profiler: &Profiler) -> u64;
+}
+
+/// Reads a [`TimeSource`].
+struct Source<S>(PhantomData<S>);
+
+impl<S: TimeSource> MetricReader for Source<S> {
+    #[inline(always)]
+    fn read(_: &Profiler) -> u64 {
+        S::read()
+    }
+}
+
+/// Reads the performance counter of the profiler's event.
+struct PerfEventReader;
+
+impl MetricReader for PerfEventReader {
+    #[inline(always)]
+    fn read(profiler: &Profiler) -> u64 {
+        crate::os::read_perf_event(profiler.perf_event_kind)
+    }
+}
+
+impl Profiler {
+    fn open_block<R: MetricReader>(
+        &mut self,
+        label: &'static str,
+        anchor_index: usize,
+        bytes: u64,
+    ) -> ProfileBlock {
+        let parent_index = std::mem::replace(&mut self.current_open_block, anchor_index);
+        self.blocks_opened += 1;
+        let serial = self.blocks_opened;
+        let parent_serial = std::mem::replace(&mut self.current_open_serial, serial);
+        let anchor = &self.anchors[anchor_index];
+        let elapsed_inclusive_prev = anchor.elapsed_inclusive;
+        let nested_hit_count_prev = anchor.nested_hit_count;
+        if anchor.hit_count == 0 {
+            self.set_label(anchor_index, label);
+        }
+        if bytes != 0 {
+            self.details[anchor_index].processed_bytes += bytes;
+        }
+        let mut block = ProfileBlock {
+            anchor_index,
+            parent_index,
+            start_counter: 0,
+            elapsed_inclusive_prev,
+            extra_start: [0; MAX_EXTRA_METRICS],
+            extra_inclusive_prev: [0; MAX_EXTRA_METRICS],
+            blocks_closed_start: self.blocks_closed,
+            nested_hit_count_prev,
+            generation: self.generation,
+            serial,
+            parent_serial,
+            #[cfg(feature = "tracing")]
+            _span: None,
+        };
+        if self.extra_metric_count != 0 {
+            self.open_extra_metrics(&mut block);
+        }
+
+        // Read the primary metric last, so that it is the closest to the profiled code.
+        block.start_counter = R::read(self);
+        block
+    }
+
+    fn close_block<R: MetricReader>(&mut self, block: &mut ProfileBlock) {
+        // Read the primary metric first, so that it is the closest to the profiled code.
+        let end_counter = R::read(self);
+        let extra_end = match self.extra_metric_count {
+            0 => None,
+            _ => Some(self.read_extra_metrics()),
+        };
+        if block.generation != self.generation {
+            self.reconcile_block(block);
+        }
+        let elapsed = end_counter - block.start_counter;
+        let nested_hit_count = self.blocks_closed - block.blocks_closed_start;
+        self.blocks_closed += 1;
+        if !self.histograms.is_empty() {
+            self.record_histogram(block.anchor_index, elapsed);
+        }
+
+        let anchor = &mut self.anchors[block.anchor_index];
+        anchor.hit_count += 1;
+        anchor.nested_hit_count = block.nested_hit_count_prev + nested_hit_count;
+        anchor.elapsed_exclusive += elapsed as i64;
+        anchor.elapsed_inclusive = block.elapsed_inclusive_prev + elapsed;
+        let new_min = elapsed < anchor.elapsed_min;
+        let new_max = elapsed > anchor.elapsed_max;
+        anchor.elapsed_min = anchor.elapsed_min.min(elapsed);
+        anchor.elapsed_max = anchor.elapsed_max.max(elapsed);
+        let new_parent = anchor.parent_index as usize != block.parent_index
+            && block.parent_index != block.anchor_index;
+        if new_min || new_max || new_parent {
+            self.record_extremes(block, nested_hit_count, new_min, new_max, new_parent);
+        }
+
+        // Account for nested calls
+        self.current_open_block = block.parent_index;
+        self.current_open_serial = block.parent_serial;
+        let parent = &mut self.anchors[block.parent_index];
+        parent.elapsed_exclusive -= elapsed as i64;
+        parent.child_hit_count += 1;
+        if let Some(extra_end) = extra_end {
+            self.close_extra_metrics(block, extra_end);
+        }
+
+        #[cfg(feature = "reporter")]
+        self.publish_for_reporter();
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn set_label(&mut self, anchor_index: usize, label: &'static str) {
+        self.details[anchor_index].label = match anchor_index {
+            OVERFLOW_ANCHOR_IDX => OVERFLOW_LABEL,
+            _ => label,
+        };
+    }
+
+    /// Records the parent of a block, and the nested blocks of a new minimum or maximum.
+    #[cold]
+    #[inline(never)]
+    fn record_extremes(
+        &mut self,
+        block: &ProfileBlock,
+        nested_hit_count: u64,
+        new_min: bool,
+        new_max: bool,
+        new_parent: bool,
+    ) {
+        let details = &mut self.details[block.anchor_index];
+        if new_min {
+            details.elapsed_min_nested_hit_count = nested_hit_count;
+        }
+        if new_max {
+            details.elapsed_max_nested_hit_count = nested_hit_count;
+        }
+        let anchor = &mut self.anchors[block.anchor_index];
+        if new_parent {
+            match anchor.parent_index as usize {
+                NO_PARENT_IDX => anchor.parent_index = block.parent_index as u32,
+                _ => anchor.several_parents = true,
+            }
+        }
+    }
+
+    /// Adjusts a block opened before a reset or a discarded block.
+    #[cold]
+    #[inline(never)]
+    fn reconcile_block(&mut self, block: &mut ProfileBlock) {
+        if !self.discarded_blocks.is_empty() {
+            self.reparent_discarded(&mut block.parent_index, &mut block.parent_serial);
+        }
+        if block.generation < self.reset_generation {
+            // The block was opened before a reset, so only the part after it is recorded.
+            block.start_counter = self.reset_at;
+            block.extra_start = self.extra_reset;
+            block.elapsed_inclusive_prev = 0;
+            block.extra_inclusive_prev = [0; MAX_EXTRA_METRICS];
+            block.blocks_closed_start = 0;
+            block.nested_hit_count_prev = 0;
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn record_histogram(&mut self, anchor_index: usize, elapsed: u64) {
+        self.histograms[anchor_index].record(elapsed);
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn open_extra_metrics(&self, block: &mut ProfileBlock) {
+        block.extra_inclusive_prev = self.details[block.anchor_index].extra_inclusive;
+        block.extra_start = self.read_extra_metrics();
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn close_extra_metrics(&mut self, block: &ProfileBlock, extra_end: [u64; MAX_EXTRA_METRICS]) {
+        // Unused extra metrics read zero both at the start and the end.
+        let extra_elapsed: [u64; MAX_EXTRA_METRICS] =
+            std::array::from_fn(|i| extra_end[i] - block.extra_start[i]);
+        let details = &mut self.details[block.anchor_index];
+        for (i, extra) in extra_elapsed.iter().enumerate() {
+            details.extra_exclusive[i] += *extra as i64;
+            details.extra_inclusive[i] = block.extra_inclusive_prev[i] + extra;
+        }
+        let parent = &mut self.details[block.parent_index];
+        for (exclusive, extra) in parent.extra_exclusive.iter_mut().zip(extra_elapsed) {
+            *exclusive -= extra as i64;
+        }
+    }
+}
@@ -0,0 +1,747 @@
+use crate::metrics::{MetricType, PerfEventKind};
+use crate::report::CallParent;
+use crate::{Error, ProfileBlock, Profiler};
+
+#[test]
+fn test_multi_metric_records_extra_metrics() {
+    Profiler::start_global_multi(&[
+        MetricType::OsClock,
+        MetricType::CpuCounter,
+        MetricType::PerfEvent(PerfEventKind::TaskClock),
+    ])
+    .unwrap();
+    {
+        let _outer = ProfileBlock::new("outer", 1);
+        let _inner = ProfileBlock::new("inner", 2);
+    }
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    assert_eq!(report.measurements.len(), 2);
+    for meas in &report.measurements {
+        let extra: Vec<_> = meas.extra.iter().flatten().collect();
+        assert_eq!(extra.len(), 2);
+        assert_eq!(extra[0].elapsed_exclusive.name(), "CPU Counter");
+        assert_eq!(extra[1].elapsed_exclusive.name(), "task-clock");
+        assert!(extra[0].elapsed_inclusive.value() >= extra[0].elapsed_exclusive.value());
+    }
+}
+
+#[test]
+fn test_multi_metric_count_is_checked() {
+    use crate::metrics::MAX_EXTRA_METRICS;
+
+    assert!(matches!(
+        Profiler::start_global_multi(&[]),
+        Err(Error::NoMetric)
+    ));
+    let metric_types = [MetricType::OsClock; MAX_EXTRA_METRICS + 2];
+    assert!(matches!(
+        Profiler::start_global_multi(&metric_types),
+        Err(Error::TooManyMetrics { .. })
+    ));
+}
+
+#[test]
+fn test_report_requires_stopped_profiler() {
+    assert!(matches!(Profiler::report(), Err(Error::NotStarted)));
+    assert!(matches!(Profiler::snapshot(), Err(Error::NotStarted)));
+
+    Profiler::start_global(MetricType::OsClock);
+    {
+        let _block = ProfileBlock::new("block", 1);
+    }
+    assert!(matches!(Profiler::report(), Err(Error::NotStopped)));
+    assert!(Profiler::snapshot().is_ok());
+
+    Profiler::stop_global();
+    assert_eq!(Profiler::report().unwrap().measurements.len(), 1);
+}
+
+#[test]
+fn test_overhead_calibration_and_subtraction() {
+    Profiler::start_global(MetricType::OsClock);
+    let overhead = Profiler::calibrate_overhead();
+    assert!(overhead.per_nested_block > 0);
+    assert!(overhead.per_nested_block >= overhead.per_block);
+    {
+        let _outer = ProfileBlock::new("outer", 1);
+        for _ in 0..10 {
+            let _inner = ProfileBlock::new("inner", 2);
+        }
+    }
+    Profiler::stop_global();
+
+    let raw = Profiler::report().unwrap();
+    Profiler::subtract_overhead(true);
+    let corrected = Profiler::report().unwrap();
+
+    assert_eq!(raw.measurements.len(), 2);
+    assert!(!raw.overhead().unwrap().subtracted);
+    assert!(corrected.overhead().unwrap().subtracted);
+    assert_eq!(
+        corrected.overhead().unwrap().instrumentation_cost.value(),
+        11 * overhead.per_nested_block
+    );
+    for (raw, corrected) in raw.measurements.iter().zip(&corrected.measurements) {
+        assert!(corrected.elapsed_exclusive.value() <= raw.elapsed_exclusive.value());
+        assert!(corrected.elapsed_inclusive.value() <= raw.elapsed_inclusive.value());
+    }
+}
+
+#[test]
+fn test_processed_bytes_accumulate() {
+    Profiler::start_global(MetricType::OsClock);
+    let buffer = [0u8; 256];
+    for _ in 0..4 {
+        crate::profile_block! { ["parse", bytes = buffer.len()]
+            std::hint::black_box(&buffer);
+        }
+    }
+    crate::profile_block! { ["idle", 900]
+        std::hint::black_box(&buffer);
+    }
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let parse = report
+        .measurements
+        .iter()
+        .find(|m| m.label == "parse")
+        .unwrap();
+    assert_eq!(parse.hit_count, 4);
+    assert_eq!(parse.processed_bytes, 1024);
+    assert!(parse.throughput().is_some());
+
+    let idle = report
+        .measurements
+        .iter()
+        .find(|m| m.label == "idle")
+        .unwrap();
+    assert_eq!(idle.processed_bytes, 0);
+    assert!(idle.throughput().is_none());
+}
+
+#[test]
+fn test_function_label() {
+    use crate::profile::{function_label, method_label};
+
+    assert_eq!(function_label("krate::free::__tuff_fn"), "free");
+    assert_eq!(function_label("krate::module::free::__tuff_fn"), "free");
+    assert_eq!(function_label("krate::Module::free::__tuff_fn"), "free");
+    assert_eq!(
+        method_label("krate::module::Type::method::__tuff_fn"),
+        "Type::method"
+    );
+    assert_eq!(
+        method_label("krate::Type<_>::method::__tuff_fn"),
+        "Type<_>::method"
+    );
+    assert_eq!(
+        function_label("<krate::Type<u8> as krate::Trait>::method::__tuff_fn"),
+        "Type<u8>::method"
+    );
+    assert_eq!(function_label("free::__tuff_fn"), "free");
+}
+
+#[test]
+fn test_trait_method_label() {
+    use crate::profile::{function_label, method_label};
+
+    assert_eq!(
+        function_label("<krate::Square as krate::Shape>::area::__tuff_fn"),
+        "Square::area"
+    );
+    assert_eq!(
+        function_label("<krate::shapes::square as krate::Shape>::area::__tuff_fn"),
+        "square::area"
+    );
+    assert_eq!(
+        function_label("<krate::Map<krate::Key, u8> as krate::Get<u8>>::get::__tuff_fn"),
+        "Map<krate::Key, u8>::get"
+    );
+    // The type of an inherent method is only kept when the function is in an `impl`.
+    assert_eq!(function_label("krate::square::area::__tuff_fn"), "area");
+    assert_eq!(
+        method_label("krate::square::area::__tuff_fn"),
+        "square::area"
+    );
+}
+
+#[test]
+fn test_instrumented_future_busy_and_wall_time() {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    use crate::Instrumented;
+
+    /// Returns pending on the first poll, and ready with `7` on the second, without sleeping.
+    struct Sleepy {
+        polled: bool,
+    }
+
+    impl Future for Sleepy {
+        type Output = u32;
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<u32> {
+            if self.polled {
+                return Poll::Ready(7);
+            }
+            self.polled = true;
+            Poll::Pending
+        }
+    }
+
+    Profiler::start_global(MetricType::OsClock);
+    let mut future = Box::pin(Instrumented::new(Sleepy { polled: false }, "sleepy", 3).bytes(64));
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // The first poll happens on another thread, and the future completes on this one.
+    let mut future = std::thread::spawn(move || {
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        future
+    })
+    .join()
+    .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(7));
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let sleepy = report
+        .measurements
+        .iter()
+        .find(|m| m.label == "sleepy")
+        .unwrap();
+    assert_eq!(sleepy.hit_count, 1);
+    assert_eq!(sleepy.completion_count(), 1);
+    // The bytes were recorded by the first poll, on the other thread.
+    assert_eq!(sleepy.processed_bytes, 0);
+    let wall = sleepy.wall_time().unwrap().as_nanos();
+    assert!(wall >= 20_000_000);
+    assert!(sleepy.elapsed_inclusive.value() < wall);
+}
+
+#[test]
+fn test_profile_expression() {
+    fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+        let value = crate::profile!("parse", input.parse::<u32>()?);
+        Ok(value)
+    }
+
+    fn first_even(values: &[u32]) -> Option<u32> {
+        for value in values {
+            crate::profile!("check", index = 901, {
+                if value.is_multiple_of(2) {
+                    return Some(*value);
+                }
+            });
+        }
+        None
+    }
+
+    Profiler::start_global(MetricType::OsClock);
+    let data = [1u8; 32];
+    let sum = crate::profile!("sum", bytes = data.len(), {
+        let total: u32 = data.iter().map(|b| *b as u32).sum();
+        total
+    });
+    assert_eq!(sum, 32);
+
+    let squares: Vec<u32> = (0..4).map(|x| crate::profile!("square", x * x)).collect();
+    assert_eq!(squares, [0, 1, 4, 9]);
+
+    for input in ["1", "x", "3"] {
+        let _ = match parse(input) {
+            Ok(value) => crate::profile!("ok", index = 902, value),
+            Err(_) => 0,
+        };
+    }
+    assert_eq!(first_even(&[1, 3, 4, 5]), Some(4));
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let hits = |label: &str| {
+        report
+            .measurements
+            .iter()
+            .find(|m| m.label == label)
+            .map(|m| m.hit_count)
+    };
+    assert_eq!(hits("sum"), Some(1));
+    assert_eq!(hits("square"), Some(4));
+    // The failed parse returns early with `?`, and still closes its block.
+    assert_eq!(hits("parse"), Some(3));
+    assert_eq!(hits("ok"), Some(2));
+    assert_eq!(hits("check"), Some(3));
+    let sum = report.measurements.iter().find(|m| m.label == "sum");
+    assert_eq!(sum.unwrap().processed_bytes, 32);
+}
+
+#[test]
+fn test_dynamic_labels() {
+    use crate::profile::label::intern;
+
+    Profiler::start_global(MetricType::OsClock);
+    for table in ["users", "orders", "users"] {
+        let name = format!("scan {table}");
+        let _block = ProfileBlock::dynamic(&name, 16);
+    }
+    for shard in [0, 1, 1, 1] {
+        crate::profile!(dyn ("shard", shard), std::hint::black_box(shard));
+    }
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let find = |label: &str| report.measurements.iter().find(|m| m.label == label);
+    let users = find("scan users").unwrap();
+    assert_eq!(users.hit_count, 2);
+    assert_eq!(users.processed_bytes, 32);
+    assert_eq!(find("scan orders").unwrap().hit_count, 1);
+    assert_eq!(find("shard[0]").unwrap().hit_count, 1);
+    assert_eq!(find("shard[1]").unwrap().hit_count, 3);
+
+    assert!(std::ptr::eq(intern("scan users"), users.label));
+}
+
+#[test]
+fn test_dynamic_labels_overflow() {
+    // A new thread, so that all of its anchors are free.
+    let report = std::thread::spawn(|| {
+        Profiler::start_global(MetricType::OsClock);
+        for key in 0..1100 {
+            let _block = ProfileBlock::dynamic(("key", key), 0);
+        }
+        drop(ProfileBlock::dynamic(("key", 0), 0));
+        Profiler::stop_global();
+        Profiler::report().unwrap()
+    })
+    .join()
+    .unwrap();
+
+    let find = |label: &str| report.measurements.iter().find(|m| m.label == label);
+    assert_eq!(report.measurements.len(), 1023);
+    assert_eq!(find("key[0]").unwrap().hit_count, 2);
+    assert_eq!(find("key[1021]").unwrap().hit_count, 1);
+    assert!(find("key[1022]").is_none());
+    assert_eq!(find("<overflow>").unwrap().hit_count, 78);
+}
+
+#[test]
+fn test_begin_and_end_tokens() {
+    use crate::profile::Token;
+
+    fn on_request() -> Token {
+        Profiler::begin("request", 11)
+    }
+
+    fn on_complete(token: Token) {
+        Profiler::end(token).unwrap();
+    }
+
+    Profiler::start_global(MetricType::OsClock);
+    let request = on_request();
+    let parse = Profiler::begin("parse", 12);
+    assert_eq!(parse.label(), "parse");
+    Profiler::end(parse).unwrap();
+    on_complete(request);
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let find = |label: &str| report.measurements.iter().find(|m| m.label == label);
+    let request = find("request").unwrap();
+    let parse = find("parse").unwrap();
+    assert_eq!(request.hit_count, 1);
+    assert_eq!(parse.hit_count, 1);
+    assert!(request.elapsed_inclusive.value() >= parse.elapsed_inclusive.value());
+}
+
+#[test]
+fn test_end_mismatched_token() {
+    Profiler::start_global(MetricType::OsClock);
+    let outer = Profiler::begin("outer", 13);
+    let inner = Profiler::begin("inner", 14);
+    assert!(matches!(
+        Profiler::end(outer),
+        Err(Error::MismatchedToken {
+            ended: "outer",
+            innermost: "inner"
+        })
+    ));
+    Profiler::end(inner).unwrap();
+
+    // Recursive blocks share their anchor, so only their order tells them apart.
+    let outer = Profiler::begin("recursive", 16);
+    let inner = Profiler::begin("recursive", 16);
+    assert!(matches!(
+        Profiler::end(outer),
+        Err(Error::MismatchedToken { .. })
+    ));
+    Profiler::end(inner).unwrap();
+    Profiler::stop_global();
+
+    // The discarded blocks are not recorded, and the blocks open in them move to the root.
+    let report = Profiler::report().unwrap();
+    let find = |label: &str| report.measurements.iter().find(|m| m.label == label);
+    assert!(find("outer").is_none());
+    assert_eq!(find("inner").unwrap().parent, Some(CallParent::Root));
+    assert_eq!(find("recursive").unwrap().hit_count, 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "dropped without `Profiler::end`")]
+fn test_unended_token() {
+    Profiler::start_global(MetricType::OsClock);
+    let _ = Profiler::begin("unended", 15);
+}
+
+#[test]
+fn test_custom_time_source() {
+    use std::cell::Cell;
+
+    use crate::Frequency;
+    use crate::metrics::{TickUnit, TimeSource};
+
+    thread_local! {
+        static NOW: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Advances by 10 nanoseconds every time it is read.
+    struct StepClock;
+
+    impl TimeSource for StepClock {
+        fn read() -> u64 {
+            NOW.with(|now| {
+                now.set(now.get() + 10);
+                now.get()
+            })
+        }
+
+        fn unit() -> TickUnit {
+            TickUnit::Nanoseconds
+        }
+
+        fn frequency() -> Frequency {
+            Frequency::from_hertz(1_000_000_000)
+        }
+    }
+
+    Profiler::start_global_with::<StepClock>();
+    for _ in 0..3 {
+        let _block = ProfileBlock::new("step", 16);
+    }
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let step = report
+        .measurements
+        .iter()
+        .find(|m| m.label == "step")
+        .unwrap();
+    assert_eq!(step.hit_count, 3);
+    assert_eq!(step.elapsed_inclusive.value(), 30);
+    assert_eq!(step.elapsed_min.value(), 10);
+    assert_eq!(step.elapsed_inclusive.name(), "OS Clock");
+}
+
+#[test]
+fn test_custom_cycle_source_frequency() {
+    use std::cell::Cell;
+
+    use crate::Frequency;
+    use crate::metrics::{ProfileMetric, TickUnit, TimeSource};
+
+    thread_local! {
+        static NOW: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Counts cycles at 1 MHz, advancing by 500 every time it is read.
+    struct SlowCounter;
+
+    impl TimeSource for SlowCounter {
+        fn read() -> u64 {
+            NOW.with(|now| {
+                now.set(now.get() + 500);
+                now.get()
+            })
+        }
+
+        fn unit() -> TickUnit {
+            TickUnit::Cycles
+        }
+
+        fn frequency() -> Frequency {
+            Frequency::from_hertz(1_000_000)
+        }
+    }
+
+    Profiler::start_global_with::<SlowCounter>();
+    drop(ProfileBlock::new("tick", 17));
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let tick = report
+        .measurements
+        .iter()
+        .find(|m| m.label == "tick")
+        .unwrap();
+    let frequency = Frequency::from_hertz(1_000_000);
+    assert_eq!(tick.elapsed_inclusive, ProfileMetric::Ticks(500, frequency));
+    assert_eq!(tick.elapsed_inclusive.as_secs_f64(), Some(0.0005));
+}
+
+mod mock {
+    use crate::metrics::MockClock;
+    use crate::profile::Overhead;
+    use crate::report::Measurement;
+    use crate::{ProfileBlock, Profiler};
+
+    fn start() {
+        MockClock::set(1_000);
+        Profiler::start_global_with::<MockClock>();
+    }
+
+    fn find<'a>(measurements: &'a [Measurement], label: &str) -> &'a Measurement {
+        measurements.iter().find(|m| m.label == label).unwrap()
+    }
+
+    /// Returns the hit count and the exclusive, inclusive, minimum and maximum values.
+    fn values(meas: &Measurement) -> (u64, u64, u64, u64, u64) {
+        (
+            meas.hit_count,
+            meas.elapsed_exclusive.value(),
+            meas.elapsed_inclusive.value(),
+            meas.elapsed_min.value(),
+            meas.elapsed_max.value(),
+        )
+    }
+
+    #[test]
+    fn test_exclusive_and_inclusive() {
+        start();
+        {
+            let _outer = ProfileBlock::new("outer", 1);
+            MockClock::advance(10);
+            {
+                let _inner = ProfileBlock::new("inner", 2);
+                MockClock::advance(20);
+            }
+            MockClock::advance(5);
+            {
+                let _inner = ProfileBlock::new("inner", 2);
+                MockClock::advance(30);
+            }
+            MockClock::advance(15);
+        }
+        MockClock::advance(20);
+        Profiler::stop_global();
+
+        let report = Profiler::report().unwrap();
+        assert_eq!(report.total_metric().unwrap(), 100);
+        assert_eq!(
+            values(find(&report.measurements, "outer")),
+            (1, 30, 80, 80, 80)
+        );
+        assert_eq!(
+            values(find(&report.measurements, "inner")),
+            (2, 50, 50, 20, 30)
+        );
+    }
+
+    #[test]
+    fn test_sibling_and_deep_nesting() {
+        start();
+        {
+            let _a = ProfileBlock::new("a", 1);
+            MockClock::advance(1);
+            {
+                let _b = ProfileBlock::new("b", 2);
+                MockClock::advance(2);
+                {
+                    let _c = ProfileBlock::new("c", 3);
+                    MockClock::advance(4);
+                }
+                MockClock::advance(8);
+            }
+            {
+                let _c = ProfileBlock::new("c", 3);
+                MockClock::advance(16);
+            }
+        }
+        Profiler::stop_global();
+
+        let report = Profiler::report().unwrap();
+        assert_eq!(values(find(&report.measurements, "a")), (1, 1, 31, 31, 31));
+        assert_eq!(values(find(&report.measurements, "b")), (1, 10, 14, 14, 14));
+        // `c` is a child of `b` once and of `a` once.
+        assert_eq!(values(find(&report.measurements, "c")), (2, 20, 20, 4, 16));
+    }
+
+    #[test]
+    fn test_recursion_is_not_counted_twice() {
+        fn recurse(depth: u32) {
+            let _block = ProfileBlock::new("recurse", 1);
+            MockClock::advance(10);
+            if depth > 0 {
+                recurse(depth - 1);
+            }
+        }
+
+        start();
+        {
+            let _main = ProfileBlock::new("main", 2);
+            recurse(2);
+            MockClock::advance(5);
+        }
+        Profiler::stop_global();
+
+        let report = Profiler::report().unwrap();
+        // The recursive calls last 10, 20 and 30, but only the outermost counts as inclusive.
+        assert_eq!(
+            values(find(&report.measurements, "recurse")),
+            (3, 30, 30, 10, 30)
+        );
+        assert_eq!(
+            values(find(&report.measurements, "main")),
+            (1, 5, 35, 35, 35)
+        );
+    }
+
+    #[test]
+    fn test_min_and_max_over_hits() {
+        start();
+        for elapsed in [7, 3, 11, 5] {
+            let _block = ProfileBlock::new("varying", 1);
+            MockClock::advance(elapsed);
+        }
+        Profiler::stop_global();
+
+        let report = Profiler::report().unwrap();
+        assert_eq!(
+            values(find(&report.measurements, "varying")),
+            (4, 26, 26, 3, 11)
+        );
+    }
+
+    #[test]
+    fn test_start_stop_cycles() {
+        start();
+        {
+            let _block = ProfileBlock::new("block", 1);
+            MockClock::advance(10);
+        }
+        Profiler::stop_global();
+        // Neither the time nor the blocks while stopped are part of the profile cycles.
+        MockClock::advance(1_000);
+        Profiler::start_global_with::<MockClock>();
+        {
+            let _block = ProfileBlock::new("block", 1);
+            MockClock::advance(30);
+        }
+        Profiler::stop_global();
+
+        let report = Profiler::report().unwrap();
+        assert_eq!(report.total_metric().unwrap(), 40);
+        assert_eq!(
+            values(find(&report.measurements, "block")),
+            (2, 40, 40, 10, 30)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_while_running() {
+        start();
+        {
+            let _block = ProfileBlock::new("block", 1);
+            MockClock::advance(10);
+        }
+        MockClock::advance(5);
+        let snapshot = Profiler::snapshot().unwrap();
+        assert_eq!(snapshot.total_metric().unwrap(), 15);
+        assert_eq!(
+            values(find(&snapshot.measurements, "block")),
+            (1, 10, 10, 10, 10)
+        );
+
+        {
+            let _block = ProfileBlock::new("block", 1);
+            MockClock::advance(20);
+        }
+        Profiler::stop_global();
+        let report = Profiler::report().unwrap();
+        assert_eq!(report.total_metric().unwrap(), 35);
+        assert_eq!(find(&report.measurements, "block").hit_count, 2);
+    }
+
+    #[test]
+    fn test_reset_between_phases() {
+        start();
+        {
+            let _warmup = ProfileBlock::new("warmup", 1);
+            MockClock::advance(100);
+        }
+        {
+            let _outer = ProfileBlock::new("outer", 2);
+            MockClock::advance(10);
+            {
+                let _inner = ProfileBlock::new("inner", 3);
+                MockClock::advance(10);
+            }
+            // Only the part of `outer` after the reset is recorded.
+            Profiler::reset();
+            MockClock::advance(5);
+            {
+                let _inner = ProfileBlock::new("inner", 3);
+                MockClock::advance(20);
+            }
+            MockClock::advance(5);
+        }
+        MockClock::advance(10);
+        Profiler::stop_global();
+
+        let report = Profiler::report().unwrap();
+        assert_eq!(report.total_metric().unwrap(), 40);
+        assert!(report.measurements.iter().all(|m| m.label != "warmup"));
+        assert_eq!(
+            values(find(&report.measurements, "outer")),
+            (1, 10, 30, 30, 30)
+        );
+        assert_eq!(
+            values(find(&report.measurements, "inner")),
+            (1, 20, 20, 20, 20)
+        );
+    }
+
+    #[test]
+    fn test_corrected_min_does_not_exceed_max() {
+        start();
+        // A short hit with no nested blocks, and a long one with ten.
+        {
+            let _block = ProfileBlock::new("block", 1);
+            MockClock::advance(100);
+        }
+        {
+            let _block = ProfileBlock::new("block", 1);
+            for _ in 0..10 {
+                let _nested = ProfileBlock::new("nested", 2);
+            }
+            MockClock::advance(120);
+        }
+        Profiler::stop_global();
+        Profiler::set_overhead(Overhead {
+            per_block: 5,
+            per_nested_block: 10,
+        });
+        Profiler::subtract_overhead(true);
+        let report = Profiler::report().unwrap();
+        Profiler::subtract_overhead(false);
+
+        let block = find(&report.measurements, "block");
+        assert_eq!(block.elapsed_max.value(), 15);
+        assert!(block.elapsed_min.value() <= block.elapsed_max.value());
+        report.write(std::io::sink()).unwrap();
+    }
+}
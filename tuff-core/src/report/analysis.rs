@@ -0,0 +1,195 @@
+use std::fmt;
+
+use super::rep::{CallParent, Measurement, ProfileReport};
+use crate::error::Result;
+
+/// Order in which [`ProfileReport::top`] ranks the blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rank {
+    /// Metric elapsed not including children blocks.
+    Exclusive,
+
+    /// Metric elapsed including children blocks.
+    Inclusive,
+
+    /// Metric elapsed including children blocks, per hit.
+    PerHit,
+}
+
+/// A block of a report, with the value it was ranked by.
+#[derive(Debug, Clone, Copy)]
+pub struct RankedBlock<'a> {
+    measurement: &'a Measurement,
+    value: u64,
+    share: Option<f64>,
+}
+
+impl<'a> RankedBlock<'a> {
+    pub fn measurement(&self) -> &'a Measurement {
+        self.measurement
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.measurement.label
+    }
+
+    /// Value the block was ranked by, in the unit of the primary metric.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Fraction of the total metric elapsed, for the values that are part of it.
+    pub fn share(&self) -> Option<f64> {
+        self.share
+    }
+}
+
+/// The fewest blocks whose exclusive metric makes up a given share of the total.
+#[derive(Debug, Clone)]
+pub struct HotBlocks<'a> {
+    blocks: Vec<RankedBlock<'a>>,
+    share: f64,
+}
+
+impl<'a> HotBlocks<'a> {
+    /// The blocks, by descending exclusive metric.
+    pub fn blocks(&self) -> &[RankedBlock<'a>] {
+        &self.blocks
+    }
+
+    /// Fraction of the total metric elapsed in the blocks.
+    pub fn share(&self) -> f64 {
+        self.share
+    }
+}
+
+impl fmt::Display for HotBlocks<'_> {
+    /// Explains where the time goes, for example
+    /// `80.0% of the time is in these 3 blocks: parse (50.0%), lex (20.0%), emit (10.0%)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.blocks.len();
+        write!(
+            f,
+            "{:.1}% of the time is in {} {count} block{}:",
+            self.share * 100.0,
+            if count == 1 { "this" } else { "these" },
+            if count == 1 { "" } else { "s" },
+        )?;
+        write_blocks(f, &self.blocks, ",")
+    }
+}
+
+/// The path from the root through the child with the largest inclusive metric, at every
+/// level of the call tree.
+#[derive(Debug, Clone)]
+pub struct CriticalPath<'a> {
+    blocks: Vec<RankedBlock<'a>>,
+}
+
+impl<'a> CriticalPath<'a> {
+    /// The blocks from the root down, with their inclusive metric.
+    pub fn blocks(&self) -> &[RankedBlock<'a>] {
+        &self.blocks
+    }
+}
+
+impl fmt::Display for CriticalPath<'_> {
+    /// Shows the path, for example `Critical path: main (100.0%) > parse (70.0%)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Critical path:")?;
+        write_blocks(f, &self.blocks, " >")
+    }
+}
+
+fn write_blocks(
+    f: &mut fmt::Formatter<'_>,
+    blocks: &[RankedBlock],
+    separator: &str,
+) -> fmt::Result {
+    for (i, block) in blocks.iter().enumerate() {
+        let separator = if i == 0 { "" } else { separator };
+        write!(f, "{separator} {}", block.label())?;
+        if let Some(share) = block.share {
+            write!(f, " ({:.1}%)", share * 100.0)?;
+        }
+    }
+    Ok(())
+}
+
+impl ProfileReport {
+    /// Returns the `n` blocks with the largest values of `rank`, largest first.
+    pub fn top(&self, rank: Rank, n: usize) -> Result<Vec<RankedBlock<'_>>> {
+        let total = self.total_metric()?;
+        let mut blocks: Vec<_> = self
+            .measurements
+            .iter()
+            .map(|meas| match rank {
+                Rank::Exclusive => ranked(meas, meas.elapsed_exclusive.value(), total),
+                Rank::Inclusive => ranked(meas, meas.elapsed_inclusive.value(), total),
+                Rank::PerHit => RankedBlock {
+                    measurement: meas,
+                    value: meas.elapsed_inclusive.value() / meas.hit_count.max(1),
+                    share: None,
+                },
+            })
+            .collect();
+        blocks.sort_by_key(|block| std::cmp::Reverse(block.value));
+        blocks.truncate(n);
+        Ok(blocks)
+    }
+
+    /// Returns the fewest blocks whose exclusive metric adds up to at least `share` of the
+    /// total metric elapsed, or all the blocks if they add up to less.
+    pub fn hot_blocks(&self, share: f64) -> Result<HotBlocks<'_>> {
+        let total = self.total_metric()?;
+        let mut hot = HotBlocks {
+            blocks: Vec::new(),
+            share: 0.0,
+        };
+        for block in self.top(Rank::Exclusive, self.measurements.len())? {
+            if hot.share >= share || block.value == 0 {
+                break;
+            }
+            hot.share += block.value as f64 / total.max(1) as f64;
+            hot.blocks.push(block);
+        }
+        Ok(hot)
+    }
+
+    /// Returns the critical path from the root, or `None` if the report has no call-tree
+    /// data, such as the report of a benchmark.
+    ///
+    /// Blocks opened in several different blocks are left out, since their metric can not
+    /// be split between their parents.
+    pub fn critical_path(&self) -> Result<Option<CriticalPath<'_>>> {
+        if self.measurements.iter().any(|meas| meas.parent.is_none()) {
+            return Ok(None);
+        }
+        let total = self.total_metric()?;
+        let mut blocks = Vec::new();
+        let mut parent = CallParent::Root;
+        // The path can not be longer than the number of blocks, since the blocks in a cycle
+        // of calls are opened in several blocks.
+        for _ in 0..self.measurements.len() {
+            let Some(child) = self
+                .measurements
+                .iter()
+                .filter(|meas| meas.parent == Some(parent))
+                .max_by_key(|meas| meas.elapsed_inclusive.value())
+            else {
+                break;
+            };
+            blocks.push(ranked(child, child.elapsed_inclusive.value(), total));
+            parent = CallParent::Anchor(child.anchor_index);
+        }
+        Ok(Some(CriticalPath { blocks }))
+    }
+}
+
+fn ranked(measurement: &Measurement, value: u64, total: u64) -> RankedBlock<'_> {
+    RankedBlock {
+        measurement,
+        value,
+        share: Some(value as f64 / total.max(1) as f64),
+    }
+}
@@ -0,0 +1,68 @@
+//! Reports emitted as structured records of a logging pipeline, one per measurement.
+//!
+//! The records carry the raw values of the measurements as fields, in the unit of the
+//! metric named by the `metric` field, without the colours of the printed table.
+use super::ProfileReport;
+
+/// Target of the records emitted for the measurements.
+pub const TARGET: &str = "tuff::report";
+
+#[cfg(feature = "log")]
+impl ProfileReport {
+    /// Emits a `log` record at `level` for every measurement of the report.
+    pub fn emit_log(&self, level: log::Level) {
+        for meas in &self.measurements {
+            log::log!(
+                target: TARGET,
+                level,
+                label = meas.label,
+                thread = self.thread(),
+                metric = meas.elapsed_exclusive.name(),
+                hit_count = meas.hit_count,
+                exclusive = meas.elapsed_exclusive.value(),
+                inclusive = meas.elapsed_inclusive.value(),
+                min = meas.elapsed_min.value(),
+                max = meas.elapsed_max.value(),
+                processed_bytes = meas.processed_bytes;
+                "profile block {}",
+                meas.label
+            );
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl ProfileReport {
+    /// Emits a `tracing` event at `level` for every measurement of the report.
+    pub fn emit_tracing(&self, level: ::tracing::Level) {
+        // The level of an event must be a constant.
+        macro_rules! emit {
+            ($level:expr, $meas:expr) => {
+                ::tracing::event!(
+                    target: TARGET,
+                    $level,
+                    label = $meas.label,
+                    thread = self.thread(),
+                    metric = $meas.elapsed_exclusive.name(),
+                    hit_count = $meas.hit_count,
+                    exclusive = $meas.elapsed_exclusive.value(),
+                    inclusive = $meas.elapsed_inclusive.value(),
+                    min = $meas.elapsed_min.value(),
+                    max = $meas.elapsed_max.value(),
+                    processed_bytes = $meas.processed_bytes,
+                    "profile block {}",
+                    $meas.label
+                )
+            };
+        }
+        for meas in &self.measurements {
+            match level {
+                ::tracing::Level::ERROR => emit!(::tracing::Level::ERROR, meas),
+                ::tracing::Level::WARN => emit!(::tracing::Level::WARN, meas),
+                ::tracing::Level::INFO => emit!(::tracing::Level::INFO, meas),
+                ::tracing::Level::DEBUG => emit!(::tracing::Level::DEBUG, meas),
+                _ => emit!(::tracing::Level::TRACE, meas),
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::io::{self, Write};
+
 use colored::{ColoredString, Colorize};
 
 pub(crate) fn format_index<T: Into<String>>(index: T) -> ColoredString {
@@ -31,3 +33,48 @@ pub(crate) fn format_pct(min: f64, max: f64, val: f64) -> ColoredString {
 
     format!("{val:05.2}").truecolor(red, green, 170).bold()
 }
+
+pub(crate) fn format_ratio(val: Option<f64>) -> ColoredString {
+    match val {
+        Some(val) => format!("{val:.2}").into(),
+        None => "-".into(),
+    }
+}
+
+/// Writes to the inner writer without the ANSI escape sequences that colour the cells.
+///
+/// The table is aligned before the sequences are dropped, since the tab writer does not
+/// count them in the width of a cell.
+pub(crate) struct Uncoloured<W> {
+    inner: W,
+    in_escape: bool,
+}
+
+impl<W: Write> Uncoloured<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            in_escape: false,
+        }
+    }
+}
+
+impl<W: Write> Write for Uncoloured<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut plain = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            match (self.in_escape, byte) {
+                (false, 0x1b) => self.in_escape = true,
+                (false, _) => plain.push(byte),
+                (true, b'm') => self.in_escape = false,
+                (true, _) => {}
+            }
+        }
+        self.inner.write_all(&plain)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use super::ProfileReport;
+use super::openmetrics::{BlockCounters, write_openmetrics};
+use crate::error::Result;
+use crate::reporter::Sink;
+
+/// Time a client has to send its request, and to take the response.
+const TIMEOUT_SECS: u64 = 1;
+
+/// Number of threads that answer the connections, so that a slow client only holds up one.
+const CONNECTION_THREADS: usize = 4;
+
+/// Counters of each thread and block, summed over the recorded reports.
+type Counters = Arc<Mutex<BTreeMap<(String, &'static str), BlockCounters>>>;
+
+/// A local HTTP server that exposes the recorded reports as OpenMetrics counters.
+///
+/// The counters are served at `/metrics`, and add up every report recorded, so the
+/// exporter should be fed reports that do not overlap, such as the intervals of the
+/// background reporter:
+///
+/// ```ignore
+/// let exporter = MetricsExporter::bind("127.0.0.1:9187")?;
+/// let reporter = Reporter::new(interval, exporter.sink()).spawn()?;
+/// ```
+pub struct MetricsExporter {
+    counters: Counters,
+    local_addr: SocketAddr,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsExporter {
+    /// Listens on `addr`, and serves the counters from background threads.
+    ///
+    /// One thread accepts the connections, and hands them to a fixed pool of threads that
+    /// answer them.
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let counters = Counters::default();
+        let stopping = Arc::new(AtomicBool::new(false));
+        let (connections, receiver) = mpsc::sync_channel(CONNECTION_THREADS);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..CONNECTION_THREADS)
+            .map(|_| {
+                let counters = counters.clone();
+                let receiver = receiver.clone();
+                std::thread::Builder::new()
+                    .name("tuff-exporter-connection".to_string())
+                    .spawn(move || answer(&receiver, &counters))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let thread = {
+            let stopping = stopping.clone();
+            std::thread::Builder::new()
+                .name("tuff-exporter".to_string())
+                .spawn(move || accept(listener, connections, workers, &stopping))?
+        };
+        Ok(Self {
+            counters,
+            local_addr,
+            stopping,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Adds the measurements of `report` to the counters.
+    pub fn record(&self, report: &ProfileReport) {
+        record(&self.counters, report);
+    }
+
+    /// Returns a sink for the background reporter, that records the report of every thread.
+    pub fn sink(&self) -> Sink {
+        let counters = self.counters.clone();
+        Sink::callback(move |interval| {
+            for thread in interval.threads() {
+                record(&counters, thread.report());
+            }
+        })
+    }
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::Release);
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        // Wake the server up from `accept`, so that it sees it is stopping.
+        let mut wake_addr = self.local_addr;
+        if wake_addr.ip().is_unspecified() {
+            wake_addr.set_ip(match wake_addr {
+                SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            });
+        }
+        if TcpStream::connect(wake_addr).is_ok() {
+            let _ = thread.join();
+        } else {
+            // The server can not be woken up, so it is detached, to stop at its next
+            // connection.
+            drop(thread);
+        }
+    }
+}
+
+/// Hands the connections to the pool, until the exporter is stopping, and then waits for
+/// the pool to answer the connections it has.
+fn accept(
+    listener: TcpListener,
+    connections: SyncSender<TcpStream>,
+    workers: Vec<JoinHandle<()>>,
+    stopping: &AtomicBool,
+) {
+    for stream in listener.incoming() {
+        if stopping.load(Ordering::Acquire) {
+            break;
+        }
+        // A failed connection only affects its client.
+        if let Ok(stream) = stream {
+            let _ = connections.send(stream);
+        }
+    }
+    drop(connections);
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+/// Answers the connections of the pool, one at a time, until the exporter is stopped.
+fn answer(connections: &Mutex<Receiver<TcpStream>>, counters: &Counters) {
+    loop {
+        // The lock is released before answering, so that the other threads take the next
+        // connections.
+        let Ok(stream) = connections
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .recv()
+        else {
+            break;
+        };
+        let _ = serve(stream, counters);
+    }
+}
+
+fn lock(counters: &Counters) -> MutexGuard<'_, BTreeMap<(String, &'static str), BlockCounters>> {
+    counters
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn record(counters: &Counters, report: &ProfileReport) {
+    let mut counters = lock(counters);
+    for (block, block_counters) in report.block_counters() {
+        counters
+            .entry((report.thread().to_string(), block))
+            .or_default()
+            .add(&block_counters);
+    }
+}
+
+/// Answers a single request, and closes the connection.
+fn serve(stream: TcpStream, counters: &Counters) -> Result<()> {
+    let timeout = Some(std::time::Duration::from_secs(TIMEOUT_SECS));
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, up to the empty line that ends them.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut writer = &stream;
+    let mut parts = request_line.split_whitespace();
+    if parts.next() != Some("GET") || parts.next() != Some("/metrics") {
+        write!(
+            writer,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )?;
+        return Ok(());
+    }
+
+    let mut body = Vec::new();
+    {
+        let counters = lock(counters);
+        let blocks = counters
+            .iter()
+            .map(|((thread, block), counters)| (thread.as_str(), *block, counters));
+        write_openmetrics(blocks, &mut body)?;
+    }
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/openmetrics-text; version=1.0.0; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    writer.write_all(&body)?;
+    Ok(())
+}
@@ -1,4 +1,23 @@
 pub mod rep;
-pub use rep::{Measurement, ProfileReport};
+pub use rep::{
+    CallParent, ExtraMeasurement, HISTOGRAM_BUCKETS, Histogram, Measurement, OverheadEstimate,
+    ProfileReport,
+};
 
-mod fmt;
+pub mod analysis;
+pub use analysis::{CriticalPath, HotBlocks, Rank, RankedBlock};
+
+pub(crate) mod fmt;
+
+pub(crate) mod openmetrics;
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub mod events;
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "http")]
+pub use http::MetricsExporter;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,191 @@
+use std::io::Write;
+
+use super::{Histogram, ProfileReport};
+use crate::error::Result;
+
+/// Counters of a profile block, in the OpenMetrics text format.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BlockCounters {
+    pub(crate) hits: u64,
+
+    /// Seconds elapsed, for blocks profiled with a metric that measures time.
+    pub(crate) exclusive_seconds: Option<f64>,
+    pub(crate) inclusive_seconds: Option<f64>,
+
+    /// Upper bound in seconds and number of hits of every histogram bucket, for blocks
+    /// profiled with a metric that measures time, if the profiler records histograms.
+    pub(crate) buckets: Option<Vec<(f64, u64)>>,
+}
+
+impl BlockCounters {
+    #[cfg(feature = "http")]
+    pub(crate) fn add(&mut self, other: &BlockCounters) {
+        self.hits += other.hits;
+        let sum = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.exclusive_seconds = sum(self.exclusive_seconds, other.exclusive_seconds);
+        self.inclusive_seconds = sum(self.inclusive_seconds, other.inclusive_seconds);
+        match (&mut self.buckets, &other.buckets) {
+            (Some(buckets), Some(other)) => {
+                for ((_, hits), (_, other_hits)) in buckets.iter_mut().zip(other) {
+                    *hits += other_hits;
+                }
+            }
+            (None, Some(other)) => self.buckets = Some(other.clone()),
+            _ => {}
+        }
+    }
+}
+
+impl ProfileReport {
+    /// Writes the hit count and the time of every block as OpenMetrics counters, labelled
+    /// by block and thread, as scraped by Prometheus.
+    ///
+    /// Blocks profiled with a metric that does not measure time, such as instructions,
+    /// only report their hit count. If the profiler records histograms, the time per hit
+    /// of every block is written as a histogram too.
+    pub fn to_openmetrics(&self, writer: impl Write) -> Result<()> {
+        let counters: Vec<_> = self.block_counters().collect();
+        write_openmetrics(
+            counters
+                .iter()
+                .map(|(block, counters)| (self.thread(), *block, counters)),
+            writer,
+        )
+    }
+
+    pub(crate) fn block_counters(&self) -> impl Iterator<Item = (&'static str, BlockCounters)> {
+        self.measurements.iter().map(|meas| {
+            (
+                meas.label,
+                BlockCounters {
+                    hits: meas.hit_count,
+                    exclusive_seconds: meas.elapsed_exclusive.as_secs_f64(),
+                    inclusive_seconds: meas.elapsed_inclusive.as_secs_f64(),
+                    buckets: meas.histogram().and_then(|histogram| {
+                        histogram
+                            .counts()
+                            .iter()
+                            .enumerate()
+                            .map(|(bucket, &hits)| {
+                                let bound = match Histogram::upper_bound(bucket) {
+                                    Some(bound) => {
+                                        meas.elapsed_inclusive.with_value(bound).as_secs_f64()?
+                                    }
+                                    None => f64::INFINITY,
+                                };
+                                Some((bound, hits))
+                            })
+                            .collect()
+                    }),
+                },
+            )
+        })
+    }
+}
+
+/// Writes the counters of each thread and block, followed by the end of the exposition.
+pub(crate) fn write_openmetrics<'a>(
+    blocks: impl Iterator<Item = (&'a str, &'a str, &'a BlockCounters)> + Clone,
+    mut writer: impl Write,
+) -> Result<()> {
+    writeln!(writer, "# TYPE tuff_block_hits counter")?;
+    writeln!(
+        writer,
+        "# HELP tuff_block_hits Number of times a profile block was closed."
+    )?;
+    for (thread, block, counters) in blocks.clone() {
+        writeln!(
+            writer,
+            "tuff_block_hits_total{{{}}} {}",
+            labels(block, thread),
+            counters.hits
+        )?;
+    }
+
+    writeln!(writer, "# TYPE tuff_block_seconds counter")?;
+    writeln!(
+        writer,
+        "# HELP tuff_block_seconds Time elapsed in a profile block."
+    )?;
+    writeln!(writer, "# UNIT tuff_block_seconds seconds")?;
+    for (thread, block, counters) in blocks.clone() {
+        let kinds = [
+            ("exclusive", counters.exclusive_seconds),
+            ("inclusive", counters.inclusive_seconds),
+        ];
+        for (kind, seconds) in kinds {
+            if let Some(seconds) = seconds {
+                writeln!(
+                    writer,
+                    "tuff_block_seconds_total{{{},kind=\"{kind}\"}} {seconds}",
+                    labels(block, thread),
+                )?;
+            }
+        }
+    }
+
+    if blocks
+        .clone()
+        .any(|(_, _, counters)| counters.buckets.is_some())
+    {
+        writeln!(writer, "# TYPE tuff_block_duration_seconds histogram")?;
+        writeln!(
+            writer,
+            "# HELP tuff_block_duration_seconds Time elapsed per hit of a profile block."
+        )?;
+        writeln!(writer, "# UNIT tuff_block_duration_seconds seconds")?;
+    }
+    for (thread, block, counters) in blocks {
+        let Some(buckets) = &counters.buckets else {
+            continue;
+        };
+        // The empty buckets above the slowest hit are left out, apart from `+Inf`.
+        let used = buckets
+            .iter()
+            .rposition(|&(_, hits)| hits > 0)
+            .map_or(0, |last| last + 1);
+        let mut cumulative = 0;
+        for (i, &(bound, hits)) in buckets.iter().enumerate() {
+            cumulative += hits;
+            if i >= used && bound.is_finite() {
+                continue;
+            }
+            let bound = if bound.is_finite() {
+                bound.to_string()
+            } else {
+                "+Inf".to_string()
+            };
+            writeln!(
+                writer,
+                "tuff_block_duration_seconds_bucket{{{},le=\"{bound}\"}} {cumulative}",
+                labels(block, thread),
+            )?;
+        }
+    }
+    writeln!(writer, "# EOF")?;
+    Ok(())
+}
+
+fn labels(block: &str, thread: &str) -> String {
+    format!(
+        "block=\"{}\",thread=\"{}\"",
+        escape_label(block),
+        escape_label(thread)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
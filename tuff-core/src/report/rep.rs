@@ -1,14 +1,29 @@
-use std::io::{self, stdout, Write};
+use std::io::{Write, stdout};
 use tabwriter::{Alignment, TabWriter};
 
-use super::fmt::{format_index, format_number, format_pct};
-use crate::metrics::ProfileMetric;
+use super::fmt::{Uncoloured, format_index, format_number, format_pct, format_ratio};
+use crate::error::{Error, Result};
+use crate::metrics::ProfileMetric::{self, CpuCounter, OsClock, PerfEvent, Ticks};
+use crate::metrics::{Duration, MAX_EXTRA_METRICS, PerfEventKind};
+
+const BYTES_PER_GIGABYTE: f64 = 1_000_000_000.0;
+
+/// Throughput in GB/s of `bytes` processed in `seconds`, if any time elapsed.
+pub(crate) fn gigabytes_per_second(bytes: u64, seconds: f64) -> Option<f64> {
+    (seconds > 0.0).then(|| bytes as f64 / BYTES_PER_GIGABYTE / seconds)
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Measurement {
     /// Label to identify the profile block.
     pub(crate) label: &'static str,
 
+    /// Index of the anchor that recorded the block, unique within a report.
+    pub(crate) anchor_index: usize,
+
+    /// Parent of the block in the call tree, if the report has call-tree data.
+    pub(crate) parent: Option<CallParent>,
+
     /// Number of times the anchor was hit.
     pub(crate) hit_count: u64,
 
@@ -23,107 +38,322 @@ pub struct Measurement {
 
     /// Maximum elapsed metric for single execution.
     pub(crate) elapsed_max: ProfileMetric,
+
+    /// Number of bytes processed over all executions.
+    pub(crate) processed_bytes: u64,
+
+    /// Metrics recorded next to the primary one, in a multi-metric profile.
+    pub(crate) extra: [Option<ExtraMeasurement>; MAX_EXTRA_METRICS],
+
+    /// Wall time of the completed futures, for blocks that profile a future.
+    pub(crate) elapsed_wall: Option<Duration>,
+
+    /// Number of completed futures, for blocks that profile a future.
+    pub(crate) completion_count: u64,
+
+    /// Distribution of the metric elapsed per hit, if the profiler records histograms.
+    pub(crate) histogram: Option<Histogram>,
+}
+
+impl Measurement {
+    /// Iterates over the exclusive values of the primary and extra metrics.
+    fn exclusive_metrics(&self) -> impl Iterator<Item = ProfileMetric> + '_ {
+        std::iter::once(self.elapsed_exclusive).chain(
+            self.extra
+                .iter()
+                .flatten()
+                .map(|extra| extra.elapsed_exclusive),
+        )
+    }
+
+    fn exclusive_perf_event(&self, kind: PerfEventKind) -> Option<u64> {
+        self.exclusive_metrics().find_map(|metric| match metric {
+            PerfEvent(k, count) if k == kind => Some(count),
+            _ => None,
+        })
+    }
+
+    fn exclusive_os_clock(&self) -> Option<u64> {
+        self.exclusive_metrics().find_map(|metric| match metric {
+            OsClock(d) => Some(d.as_nanos()),
+            _ => None,
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn hit_count(&self) -> u64 {
+        self.hit_count
+    }
+
+    /// Parent of the block in the call tree, if the report has call-tree data.
+    ///
+    /// The anchors of the parents are the ones returned by [`Measurement::anchor_index`].
+    pub fn parent(&self) -> Option<CallParent> {
+        self.parent
+    }
+
+    pub fn anchor_index(&self) -> usize {
+        self.anchor_index
+    }
+
+    /// Instructions per cycle, not including children blocks.
+    ///
+    /// Requires both `PerfEventKind::Instructions` and `PerfEventKind::Cycles` to be recorded.
+    pub fn instructions_per_cycle(&self) -> Option<f64> {
+        let instructions = self.exclusive_perf_event(PerfEventKind::Instructions)?;
+        let cycles = self.exclusive_perf_event(PerfEventKind::Cycles)?;
+        (cycles != 0).then(|| instructions as f64 / cycles as f64)
+    }
+
+    /// Throughput in GB/s, over the metric elapsed including children blocks.
+    ///
+    /// Requires the block to process bytes, and the primary metric to measure time.
+    pub fn throughput(&self) -> Option<f64> {
+        if self.processed_bytes == 0 {
+            return None;
+        }
+        gigabytes_per_second(self.processed_bytes, self.elapsed_inclusive.as_secs_f64()?)
+    }
+
+    /// Wall time from the first poll to the completion of the profiled futures.
+    ///
+    /// The busy time of the futures, while they were being polled, is the inclusive metric.
+    pub fn wall_time(&self) -> Option<Duration> {
+        self.elapsed_wall
+    }
+
+    /// Number of profiled futures that completed.
+    pub fn completion_count(&self) -> u64 {
+        self.completion_count
+    }
+
+    /// Distribution of the primary metric elapsed per hit, including children blocks.
+    ///
+    /// Requires [`crate::Profiler::record_histograms`].
+    pub fn histogram(&self) -> Option<&Histogram> {
+        self.histogram.as_ref()
+    }
+
+    /// Effective CPU frequency in GHz, not including children blocks.
+    ///
+    /// Requires both `PerfEventKind::Cycles` and the OS clock to be recorded.
+    pub fn effective_ghz(&self) -> Option<f64> {
+        let cycles = self.exclusive_perf_event(PerfEventKind::Cycles)?;
+        let nanos = self.exclusive_os_clock()?;
+        (nanos != 0).then(|| cycles as f64 / nanos as f64)
+    }
+}
+
+/// The block that a profile block was opened in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallParent {
+    /// Opened outside any other block.
+    Root,
+
+    /// Always opened in the block of the anchor, apart from recursive hits.
+    Anchor(usize),
+
+    /// Opened in different blocks.
+    Several,
+}
+
+/// Number of buckets of a [`Histogram`].
+pub const HISTOGRAM_BUCKETS: usize = 48;
+
+/// Number of hits of a block by the primary metric elapsed, in buckets that double in width.
+///
+/// Bucket `i` counts the hits that took at most `2^i` in the unit of the primary metric,
+/// and more than the bound of the bucket before it. The last bucket has no upper bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Histogram {
+    counts: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl Histogram {
+    pub(crate) const fn new() -> Self {
+        Self {
+            counts: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn record(&mut self, elapsed: u64) {
+        let bucket = (u64::BITS - elapsed.saturating_sub(1).leading_zeros()) as usize;
+        self.counts[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+    }
+
+    /// Number of hits in each bucket.
+    pub fn counts(&self) -> &[u64; HISTOGRAM_BUCKETS] {
+        &self.counts
+    }
+
+    /// Upper bound of a bucket, in the unit of the primary metric, or `None` for the last.
+    pub fn upper_bound(bucket: usize) -> Option<u64> {
+        (bucket < HISTOGRAM_BUCKETS - 1).then(|| 1 << bucket)
+    }
+
+    /// Returns the hits since `baseline`, an earlier copy of the histogram.
+    #[cfg(feature = "reporter")]
+    pub(crate) fn since(&self, baseline: &Histogram) -> Self {
+        Self {
+            counts: std::array::from_fn(|i| self.counts[i] - baseline.counts[i]),
+        }
+    }
+}
+
+/// Values of one of the extra metrics of a multi-metric profile.
+#[derive(Debug, Clone, Copy)]
+pub struct ExtraMeasurement {
+    /// Metric elapsed not including children blocks.
+    pub(crate) elapsed_exclusive: ProfileMetric,
+
+    /// Metric elapsed including children blocks.
+    pub(crate) elapsed_inclusive: ProfileMetric,
+}
+
+/// Estimated cost of the profiler's instrumentation, in the primary metric.
+#[derive(Debug, Clone, Copy)]
+pub struct OverheadEstimate {
+    /// Metric elapsed that an empty block records for itself.
+    pub(crate) per_block: ProfileMetric,
+
+    /// Metric elapsed that opening and closing an empty block adds to its parent.
+    pub(crate) per_nested_block: ProfileMetric,
+
+    /// Estimated metric elapsed in opening and closing all the profile blocks.
+    pub(crate) instrumentation_cost: ProfileMetric,
+
+    /// Whether the overhead was subtracted from the measurements.
+    pub(crate) subtracted: bool,
 }
 
 pub struct ProfileReport {
+    /// Name of the thread that was profiled, or its id if it is unnamed.
+    thread: String,
     metric_init: ProfileMetric,
     metric_final: ProfileMetric,
-    measurements: Vec<Measurement>,
+    pub(crate) measurements: Vec<Measurement>,
+    overhead: Option<OverheadEstimate>,
 }
 
 impl ProfileReport {
     pub(crate) fn new(metric_init: ProfileMetric, metric_final: ProfileMetric) -> Self {
         Self {
+            thread: thread_name(&std::thread::current()),
             metric_init,
             metric_final,
             measurements: Vec::new(),
+            overhead: None,
         }
     }
 
-    fn calculate_transpose(&self) -> Columns {
-        use ProfileMetric::{CpuCounter, OsClock};
+    pub fn thread(&self) -> &str {
+        &self.thread
+    }
+
+    /// Sets the thread that was profiled, for a report built on another thread.
+    #[cfg(feature = "reporter")]
+    pub(crate) fn set_thread(&mut self, thread: &std::thread::Thread) {
+        self.thread = thread_name(thread);
+    }
+
+    pub(crate) fn set_overhead(&mut self, overhead: OverheadEstimate) {
+        self.overhead = Some(overhead);
+    }
+
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    pub fn overhead(&self) -> Option<OverheadEstimate> {
+        self.overhead
+    }
 
+    fn calculate_transpose(&self) -> Result<Columns> {
         let mut transpose = Columns::new();
+        let total = self.total_metric()? as f64;
 
         for meas in &self.measurements {
             transpose.hit_count.insert_value(meas.hit_count);
 
-            match meas.elapsed_inclusive {
-                CpuCounter(c) => {
-                    transpose.elapsed_inclusive.insert_value(c.cycles());
-                    let proportion = c.cycles() as f64 / self.total_metric() as f64 * 100.0;
-                    transpose
-                        .proportion_inclusive
-                        .insert_value(proportion as u64);
-                }
-                OsClock(d) => {
-                    transpose.elapsed_inclusive.insert_value(d.as_nanos());
-                    let proportion = (d.as_nanos() as f64 / self.total_metric() as f64) * 100.0;
-                    transpose
-                        .proportion_inclusive
-                        .insert_value(proportion as u64);
-                }
-            }
+            let inclusive = meas.elapsed_inclusive.value();
+            transpose.elapsed_inclusive.insert_value(inclusive);
+            transpose
+                .proportion_inclusive
+                .insert_value((inclusive as f64 / total * 100.0) as u64);
 
-            match meas.elapsed_exclusive {
-                CpuCounter(c) => {
-                    transpose.elapsed_exclusive.insert_value(c.cycles());
-                    transpose.proportion_exclusive.insert_value(
-                        (c.cycles() as f64 / self.total_metric() as f64 * 100.0) as u64,
-                    );
-                }
-                OsClock(d) => {
-                    transpose.elapsed_exclusive.insert_value(d.as_nanos());
-                    transpose.proportion_exclusive.insert_value(
-                        (d.as_nanos() as f64 / self.total_metric() as f64 * 100.0) as u64,
-                    );
-                }
-            }
+            let exclusive = meas.elapsed_exclusive.value();
+            transpose.elapsed_exclusive.insert_value(exclusive);
+            transpose
+                .proportion_exclusive
+                .insert_value((exclusive as f64 / total * 100.0) as u64);
 
-            match meas.elapsed_min {
-                CpuCounter(c) => transpose.elapsed_min.insert_value(c.cycles()),
-                OsClock(d) => transpose.elapsed_min.insert_value(d.as_nanos()),
-            }
+            transpose.elapsed_min.insert_value(meas.elapsed_min.value());
+            transpose.elapsed_max.insert_value(meas.elapsed_max.value());
 
-            match meas.elapsed_max {
-                CpuCounter(c) => transpose.elapsed_max.insert_value(c.cycles()),
-                OsClock(d) => transpose.elapsed_max.insert_value(d.as_nanos()),
+            for (i, extra) in meas.extra.iter().flatten().enumerate() {
+                if transpose.extra_exclusive.len() <= i {
+                    transpose.extra_exclusive.push(Column::new());
+                    transpose.extra_inclusive.push(Column::new());
+                }
+                transpose.extra_exclusive[i].insert_value(extra.elapsed_exclusive.value());
+                transpose.extra_inclusive[i].insert_value(extra.elapsed_inclusive.value());
             }
+            transpose
+                .instructions_per_cycle
+                .push(meas.instructions_per_cycle());
+            transpose.effective_ghz.push(meas.effective_ghz());
+            transpose.processed_bytes.insert_value(meas.processed_bytes);
+            transpose.throughput.push(meas.throughput());
+            let wall = meas.elapsed_wall.map_or(0, |wall| wall.as_nanos());
+            transpose.elapsed_wall.insert_value(wall);
+            transpose
+                .elapsed_wall_per_call
+                .insert_value(wall / meas.completion_count.max(1));
 
-            match (meas.elapsed_min, meas.elapsed_max) {
-                (CpuCounter(min), CpuCounter(max)) => {
-                    transpose.range.insert_value(max.cycles() - min.cycles())
-                }
-                (OsClock(min), OsClock(max)) => transpose
-                    .range
-                    .insert_value(max.as_nanos() - min.as_nanos()),
-                _ => unimplemented!(),
-            };
+            transpose
+                .range
+                .insert_value(metric_difference(meas.elapsed_min, meas.elapsed_max)?);
         }
         debug_assert_eq!(transpose.len(), self.measurements.len());
-        transpose
+        Ok(transpose)
     }
 
-    fn total_metric(&self) -> u64 {
-        use ProfileMetric::{CpuCounter, OsClock};
-
-        match (self.metric_init, self.metric_final) {
-            (OsClock(init), OsClock(fin)) => fin.as_nanos() - init.as_nanos(),
-            (CpuCounter(init), CpuCounter(fin)) => fin.cycles() - init.cycles(),
-            _ => unimplemented!("This should not be reached"),
-        }
+    pub(crate) fn total_metric(&self) -> Result<u64> {
+        metric_difference(self.metric_init, self.metric_final)
     }
 
     pub(crate) fn push_measurement(&mut self, meas: Measurement) {
         self.measurements.push(meas)
     }
 
-    pub fn to_csv(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+    /// Names of the extra metrics, in the order they were recorded.
+    fn extra_metric_names(&self) -> Vec<&'static str> {
+        self.measurements
+            .first()
+            .map(|meas| {
+                meas.extra
+                    .iter()
+                    .flatten()
+                    .map(|extra| extra.elapsed_exclusive.name())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn to_csv(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
         let file = std::fs::File::create(path)?;
         let mut writer = std::io::BufWriter::new(file);
-        writeln!(&mut writer, "label,elapsed_exc,elapsed_inc,minimum,maximum")?;
+        write!(&mut writer, "label,elapsed_exc,elapsed_inc,minimum,maximum")?;
+        for name in self.extra_metric_names() {
+            write!(&mut writer, ",{name}_exc,{name}_inc")?;
+        }
+        writeln!(&mut writer)?;
         for meas in self.measurements.iter() {
-            writeln!(
+            write!(
                 &mut writer,
                 "{},{:?},{:?},{:?},{:?}",
                 meas.label,
@@ -132,16 +362,71 @@ impl ProfileReport {
                 meas.elapsed_min,
                 meas.elapsed_max
             )?;
+            for extra in meas.extra.iter().flatten() {
+                write!(
+                    &mut writer,
+                    ",{},{}",
+                    extra.elapsed_exclusive.value(),
+                    extra.elapsed_inclusive.value()
+                )?;
+            }
+            writeln!(&mut writer)?;
         }
         Ok(())
     }
 
-    pub fn print(self) -> io::Result<()> {
-        let transposed = self.calculate_transpose();
+    pub fn print(self) -> Result<()> {
+        self.write(stdout().lock())
+    }
+
+    /// Writes the report table, as printed by [`ProfileReport::print`], to `writer`.
+    ///
+    /// The cells are coloured when the standard output is a terminal.
+    pub fn write(&self, writer: impl Write) -> Result<()> {
+        self.write_table(writer)
+    }
+
+    /// Writes the report table to `writer` without colour, as for a file.
+    pub fn write_plain(&self, writer: impl Write) -> Result<()> {
+        self.write_table(Uncoloured::new(writer))
+    }
 
-        let stdout = stdout().lock();
-        let mut tabwriter = TabWriter::new(stdout).alignment(Alignment::Right);
-        writeln!(
+    fn write_table(&self, mut writer: impl Write) -> Result<()> {
+        let transposed = self.calculate_transpose()?;
+        let extra_metric_names = self.extra_metric_names();
+        let show_ipc = transposed
+            .instructions_per_cycle
+            .iter()
+            .any(Option::is_some);
+        let show_ghz = transposed.effective_ghz.iter().any(Option::is_some);
+        let show_bytes = transposed.processed_bytes.max_value > 0;
+        let show_wall = self.measurements.iter().any(|m| m.elapsed_wall.is_some());
+
+        if let Some(overhead) = self.overhead {
+            let proportion =
+                overhead.instrumentation_cost.value() as f64 / self.total_metric()? as f64 * 100.0;
+            writeln!(
+                &mut writer,
+                "\n{} {} per block, {} per nested block",
+                format_index("Estimated Overhead:"),
+                overhead.per_block.value(),
+                overhead.per_nested_block.value(),
+            )?;
+            writeln!(
+                &mut writer,
+                "{} {} ({proportion:.2}% of total){}",
+                format_index("Instrumentation Cost:"),
+                overhead.instrumentation_cost.value(),
+                if overhead.subtracted {
+                    ", subtracted from the measurements"
+                } else {
+                    ""
+                },
+            )?;
+        }
+
+        let mut tabwriter = TabWriter::new(writer).alignment(Alignment::Right);
+        write!(
             &mut tabwriter,
             "\n{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
             format_index("Label"),
@@ -155,11 +440,42 @@ impl ProfileReport {
             format_index("Minimum"),
             format_index("Maximum"),
             format_index("Range"),
-        )
-        .expect("Failed to create table column index");
+        )?;
+        for name in &extra_metric_names {
+            write!(
+                &mut tabwriter,
+                "\t{}\t{}",
+                format_index(format!("{name} Excl")),
+                format_index(format!("{name} Incl")),
+            )?;
+        }
+        if show_ipc {
+            write!(&mut tabwriter, "\t{}", format_index("IPC"))?;
+        }
+        if show_ghz {
+            write!(&mut tabwriter, "\t{}", format_index("GHz"))?;
+        }
+        if show_bytes {
+            write!(
+                &mut tabwriter,
+                "\t{}\t{}",
+                format_index("Bytes"),
+                format_index("Throughput (GB/s)")
+            )?;
+        }
+        if show_wall {
+            write!(
+                &mut tabwriter,
+                "\t{}\t{}\t{}",
+                format_index("Calls"),
+                format_index("Wall (ns)"),
+                format_index("Wall Per Call (ns)")
+            )?;
+        }
+        writeln!(&mut tabwriter)?;
 
         for i in 0..transposed.len() {
-            writeln!(
+            write!(
                 &mut tabwriter,
                 "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
                 format_index(self.measurements[i].label),
@@ -210,8 +526,96 @@ impl ProfileReport {
                     transposed.range.values[i],
                 )
             )?;
+            for (exclusive, inclusive) in transposed
+                .extra_exclusive
+                .iter()
+                .zip(&transposed.extra_inclusive)
+            {
+                write!(
+                    &mut tabwriter,
+                    "\t{}\t{}",
+                    format_number(
+                        exclusive.min_value,
+                        exclusive.max_value,
+                        exclusive.values[i]
+                    ),
+                    format_number(
+                        inclusive.min_value,
+                        inclusive.max_value,
+                        inclusive.values[i]
+                    ),
+                )?;
+            }
+            if show_ipc {
+                write!(
+                    &mut tabwriter,
+                    "\t{}",
+                    format_ratio(transposed.instructions_per_cycle[i])
+                )?;
+            }
+            if show_ghz {
+                write!(
+                    &mut tabwriter,
+                    "\t{}",
+                    format_ratio(transposed.effective_ghz[i])
+                )?;
+            }
+            if show_bytes {
+                write!(
+                    &mut tabwriter,
+                    "\t{}\t{}",
+                    format_number(
+                        transposed.processed_bytes.min_value,
+                        transposed.processed_bytes.max_value,
+                        transposed.processed_bytes.values[i]
+                    ),
+                    format_ratio(transposed.throughput[i])
+                )?;
+            }
+            if show_wall {
+                write!(
+                    &mut tabwriter,
+                    "\t{}\t{}\t{}",
+                    self.measurements[i].completion_count,
+                    format_number(
+                        transposed.elapsed_wall.min_value,
+                        transposed.elapsed_wall.max_value,
+                        transposed.elapsed_wall.values[i]
+                    ),
+                    format_number(
+                        transposed.elapsed_wall_per_call.min_value,
+                        transposed.elapsed_wall_per_call.max_value,
+                        transposed.elapsed_wall_per_call.values[i]
+                    ),
+                )?;
+            }
+            writeln!(&mut tabwriter)?;
+        }
+        Ok(tabwriter.flush()?)
+    }
+}
+
+/// Returns the metric elapsed from `first` to `last`, which must be in the same metric.
+/// Name of the thread, or its id if it is unnamed.
+fn thread_name(thread: &std::thread::Thread) -> String {
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+fn metric_difference(first: ProfileMetric, last: ProfileMetric) -> Result<u64> {
+    match (first, last) {
+        (OsClock(first), OsClock(last)) => Ok(last.as_nanos().saturating_sub(first.as_nanos())),
+        (CpuCounter(first), CpuCounter(last)) => Ok(last.cycles().saturating_sub(first.cycles())),
+        (PerfEvent(kind_first, first), PerfEvent(kind_last, last)) if kind_first == kind_last => {
+            Ok(last.saturating_sub(first))
         }
-        tabwriter.flush()
+        (Ticks(first, _), Ticks(last, _)) => Ok(last.saturating_sub(first)),
+        _ => Err(Error::MixedMetrics {
+            expected: first.name(),
+            found: last.name(),
+        }),
     }
 }
 
@@ -225,6 +629,14 @@ struct Columns {
     elapsed_min: Column,
     elapsed_max: Column,
     range: Column,
+    extra_exclusive: Vec<Column>,
+    extra_inclusive: Vec<Column>,
+    instructions_per_cycle: Vec<Option<f64>>,
+    effective_ghz: Vec<Option<f64>>,
+    processed_bytes: Column,
+    throughput: Vec<Option<f64>>,
+    elapsed_wall: Column,
+    elapsed_wall_per_call: Column,
 }
 
 impl Columns {
@@ -238,6 +650,14 @@ impl Columns {
             elapsed_min: Column::new(),
             elapsed_max: Column::new(),
             range: Column::new(),
+            extra_exclusive: Vec::new(),
+            extra_inclusive: Vec::new(),
+            instructions_per_cycle: Vec::new(),
+            effective_ghz: Vec::new(),
+            processed_bytes: Column::new(),
+            throughput: Vec::new(),
+            elapsed_wall: Column::new(),
+            elapsed_wall_per_call: Column::new(),
         }
     }
 
@@ -251,6 +671,20 @@ impl Columns {
                 && self.elapsed_min.len() == len
                 && self.elapsed_max.len() == len
                 && self.range.len() == len
+                && self
+                    .extra_exclusive
+                    .iter()
+                    .all(|column| column.len() == len)
+                && self
+                    .extra_inclusive
+                    .iter()
+                    .all(|column| column.len() == len)
+                && self.instructions_per_cycle.len() == len
+                && self.effective_ghz.len() == len
+                && self.processed_bytes.len() == len
+                && self.throughput.len() == len
+                && self.elapsed_wall.len() == len
+                && self.elapsed_wall_per_call.len() == len
         );
         len
     }
@@ -0,0 +1,360 @@
+use std::io::Write;
+
+use crate::Error;
+use crate::metrics::{MetricType, MockClock};
+use crate::report::fmt::Uncoloured;
+use crate::report::{CallParent, ProfileReport, Rank};
+use crate::{ProfileBlock, Profiler};
+
+fn profile() {
+    MockClock::set(0);
+    Profiler::start_global_with::<MockClock>();
+    {
+        let _outer = ProfileBlock::new("outer", 1);
+        MockClock::advance(10);
+        for elapsed in [20, 30] {
+            let _inner = ProfileBlock::with_bytes("inner", 2, 1_000);
+            MockClock::advance(elapsed);
+        }
+        MockClock::advance(20);
+    }
+    MockClock::advance(20);
+    Profiler::stop_global();
+}
+
+#[test]
+fn test_report_table() {
+    profile();
+    let mut output = Vec::new();
+    Profiler::report()
+        .unwrap()
+        .write_plain(&mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    let expected = "
+Label  Hit Count  Elapsed Excl  Elapsed Excl Per Hit  Proportion Excl  Elapsed Incl  Elapsed Incl Per Hit  Proportion Incl  Minimum  Maximum  Range  Bytes  Throughput (GB/s)
+outer          1            30                    30            30.00            80                    80            80.00       80       80      0      0  -
+inner          2            50                    25            50.00            50                    25            50.00       20       30     10   2000  40.00
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_uncoloured_writer() {
+    let mut output = Vec::new();
+    let mut writer = Uncoloured::new(&mut output);
+    // A sequence can be split across writes.
+    writer.write_all(b"\x1b[1;38;2;1;2").unwrap();
+    writer.write_all(b";3mred\x1b[0m plain").unwrap();
+    assert_eq!(output, b"red plain");
+}
+
+#[test]
+fn test_report_csv() {
+    profile();
+    let path = std::env::temp_dir().join(format!("tuff-report-{}.csv", std::process::id()));
+    Profiler::report().unwrap().to_csv(&path).unwrap();
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        csv,
+        "label,elapsed_exc,elapsed_inc,minimum,maximum
+outer,OsClock(Duration(30)),OsClock(Duration(80)),OsClock(Duration(80)),OsClock(Duration(80))
+inner,OsClock(Duration(50)),OsClock(Duration(50)),OsClock(Duration(20)),OsClock(Duration(30))
+"
+    );
+}
+
+#[test]
+fn test_report_mixed_metrics() {
+    let report = ProfileReport::new(
+        MetricType::OsClock.to_metric(0),
+        MetricType::CpuCounter.to_metric(100),
+    );
+    let mut output = Vec::new();
+    assert!(matches!(
+        report.write(&mut output),
+        Err(Error::MixedMetrics {
+            expected: "OS Clock",
+            found: "CPU Counter"
+        })
+    ));
+}
+
+#[test]
+fn test_report_openmetrics() {
+    profile();
+    let mut output = Vec::new();
+    Profiler::report()
+        .unwrap()
+        .to_openmetrics(&mut output)
+        .unwrap();
+    let thread = std::thread::current().name().unwrap().to_string();
+    let expected = r#"# TYPE tuff_block_hits counter
+# HELP tuff_block_hits Number of times a profile block was closed.
+tuff_block_hits_total{block="outer",thread="THREAD"} 1
+tuff_block_hits_total{block="inner",thread="THREAD"} 2
+# TYPE tuff_block_seconds counter
+# HELP tuff_block_seconds Time elapsed in a profile block.
+# UNIT tuff_block_seconds seconds
+tuff_block_seconds_total{block="outer",thread="THREAD",kind="exclusive"} 0.00000003
+tuff_block_seconds_total{block="outer",thread="THREAD",kind="inclusive"} 0.00000008
+tuff_block_seconds_total{block="inner",thread="THREAD",kind="exclusive"} 0.00000005
+tuff_block_seconds_total{block="inner",thread="THREAD",kind="inclusive"} 0.00000005
+# EOF
+"#;
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        expected.replace("THREAD", &thread)
+    );
+}
+
+#[test]
+fn test_report_openmetrics_histograms() {
+    Profiler::record_histograms(true);
+    profile();
+    let report = Profiler::report().unwrap();
+    let inner = report
+        .measurements()
+        .iter()
+        .find(|meas| meas.label() == "inner")
+        .unwrap();
+    // Both hits, of 20 and 30 ns, are in the bucket up to 32 ns.
+    let counts = inner.histogram().unwrap().counts();
+    assert_eq!(counts[5], 2);
+    assert_eq!(counts.iter().sum::<u64>(), 2);
+
+    let mut output = Vec::new();
+    report.to_openmetrics(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let thread = std::thread::current().name().unwrap().to_string();
+    let inner_buckets: Vec<_> = output
+        .lines()
+        .filter(|line| line.starts_with("tuff_block_duration_seconds_bucket{block=\"inner\""))
+        .map(|line| line.replace(&thread, "THREAD"))
+        .collect();
+    assert!(output.contains("# TYPE tuff_block_duration_seconds histogram\n"));
+    assert_eq!(inner_buckets.len(), 7);
+    assert_eq!(
+        inner_buckets[4],
+        r#"tuff_block_duration_seconds_bucket{block="inner",thread="THREAD",le="0.000000016"} 0"#
+    );
+    assert_eq!(
+        inner_buckets[5],
+        r#"tuff_block_duration_seconds_bucket{block="inner",thread="THREAD",le="0.000000032"} 2"#
+    );
+    assert_eq!(
+        inner_buckets[6],
+        r#"tuff_block_duration_seconds_bucket{block="inner",thread="THREAD",le="+Inf"} 2"#
+    );
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_metrics_exporter() {
+    use std::io::{Read, Write};
+
+    use crate::report::MetricsExporter;
+
+    profile();
+    let exporter = MetricsExporter::bind("127.0.0.1:0").unwrap();
+    let report = Profiler::report().unwrap();
+    exporter.record(&report);
+    exporter.record(&report);
+
+    let get = |path: &str| {
+        let mut stream = std::net::TcpStream::connect(exporter.local_addr()).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    // A client that never sends its request does not hold the others up.
+    let _idle = std::net::TcpStream::connect(exporter.local_addr()).unwrap();
+    let response = get("/metrics");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: application/openmetrics-text"));
+    let thread = std::thread::current().name().unwrap().to_string();
+    assert!(response.contains(&format!(
+        r#"tuff_block_hits_total{{block="inner",thread="{thread}"}} 4"#
+    )));
+    assert!(response.ends_with("# EOF\n"));
+    assert!(get("/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_report_emit_log() {
+    use std::sync::Mutex;
+
+    use crate::report::events::TARGET;
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Recorder;
+
+    impl log::Log for Recorder {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            if record.target() != TARGET {
+                return;
+            }
+            let field = |key: &str| record.key_values().get(key.into()).unwrap().to_string();
+            RECORDS.lock().unwrap().push(format!(
+                "{} {} label={} hit_count={} exclusive={} inclusive={}",
+                record.level(),
+                record.args(),
+                field("label"),
+                field("hit_count"),
+                field("exclusive"),
+                field("inclusive"),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    log::set_logger(&Recorder).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    profile();
+    Profiler::report().unwrap().emit_log(log::Level::Debug);
+
+    assert_eq!(
+        *RECORDS.lock().unwrap(),
+        [
+            "DEBUG profile block outer label=outer hit_count=1 exclusive=30 inclusive=80",
+            "DEBUG profile block inner label=inner hit_count=2 exclusive=50 inclusive=50",
+        ]
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_report_emit_tracing() {
+    use std::sync::{Arc, Mutex};
+
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    use crate::report::events::TARGET;
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl<S: ::tracing::Subscriber> Layer<S> for Recorder {
+        fn on_event(&self, event: &::tracing::Event<'_>, _ctx: Context<'_, S>) {
+            if event.metadata().target() != TARGET {
+                return;
+            }
+            let mut fields = vec![event.metadata().level().to_string()];
+            event.record(
+                &mut |field: &::tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    if ["message", "label", "hit_count", "exclusive"].contains(&field.name()) {
+                        fields.push(format!("{}={value:?}", field.name()));
+                    }
+                },
+            );
+            self.0.lock().unwrap().push(fields.join(" "));
+        }
+    }
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(Recorder(events.clone()));
+    profile();
+    ::tracing::subscriber::with_default(subscriber, || {
+        Profiler::report()
+            .unwrap()
+            .emit_tracing(::tracing::Level::WARN);
+    });
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "WARN message=profile block outer label=\"outer\" hit_count=1 exclusive=30",
+            "WARN message=profile block inner label=\"inner\" hit_count=2 exclusive=50",
+        ]
+    );
+}
+
+/// Profiles a call tree where `helper` is called from both `parse` and `emit`.
+fn profile_call_tree() {
+    MockClock::set(0);
+    Profiler::start_global_with::<MockClock>();
+    {
+        let _main = ProfileBlock::new("main", 1);
+        MockClock::advance(5);
+        {
+            let _parse = ProfileBlock::new("parse", 2);
+            MockClock::advance(10);
+            {
+                let _lex = ProfileBlock::new("lex", 3);
+                MockClock::advance(40);
+            }
+            let _helper = ProfileBlock::new("helper", 5);
+            MockClock::advance(5);
+        }
+        {
+            let _emit = ProfileBlock::new("emit", 4);
+            MockClock::advance(20);
+            let _helper = ProfileBlock::new("helper", 5);
+            MockClock::advance(5);
+        }
+        MockClock::advance(5);
+    }
+    MockClock::advance(10);
+    Profiler::stop_global();
+}
+
+#[test]
+fn test_report_top_blocks() {
+    profile_call_tree();
+    let report = Profiler::report().unwrap();
+    let top = |rank, n| {
+        report
+            .top(rank, n)
+            .unwrap()
+            .iter()
+            .map(|block| (block.label(), block.value()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(top(Rank::Exclusive, 2), [("lex", 40), ("emit", 20)]);
+    assert_eq!(top(Rank::Inclusive, 2), [("main", 90), ("parse", 55)]);
+    assert_eq!(
+        top(Rank::PerHit, 5),
+        [
+            ("main", 90),
+            ("parse", 55),
+            ("lex", 40),
+            ("emit", 25),
+            ("helper", 5)
+        ]
+    );
+
+    let hot = report.hot_blocks(0.5).unwrap();
+    assert_eq!(
+        hot.to_string(),
+        "60.0% of the time is in these 2 blocks: lex (40.0%), emit (20.0%)"
+    );
+}
+
+#[test]
+fn test_report_critical_path() {
+    profile_call_tree();
+    let report = Profiler::report().unwrap();
+    let helper = report
+        .measurements()
+        .iter()
+        .find(|m| m.label() == "helper")
+        .unwrap();
+    assert_eq!(helper.parent(), Some(CallParent::Several));
+
+    // `helper` is left out, since its time can not be split between `parse` and `emit`.
+    let path = report.critical_path().unwrap().unwrap();
+    assert_eq!(
+        path.to_string(),
+        "Critical path: main (90.0%) > parse (55.0%) > lex (40.0%)"
+    );
+}
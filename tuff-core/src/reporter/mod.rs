@@ -0,0 +1,251 @@
+//! A background thread that publishes a profile report per interval.
+//!
+//! The profilers are thread-local, so the reporter does not read them. At the end of every
+//! interval it moves to a new epoch, and each profiled thread publishes a copy of its
+//! measurements the next time it closes a profile block. The reporter thread reports each
+//! copy relative to the thread's last one. The measurements themselves are not reset, so
+//! [`crate::Profiler::report`] still covers the whole run. The profiled threads pay for one
+//! relaxed atomic load per block, which is why the reporter is behind the `reporter`
+//! feature, and for the copy of their anchors once per interval. A thread that closes no
+//! block after an interval ends is reported in a later interval, with the measurements of
+//! all the intervals it missed.
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{JoinHandle, ThreadId};
+
+use crate::error::{Error, Result};
+use crate::metrics::{Duration, Instant};
+use crate::profile::Profiler;
+use crate::profile::profiler::{IntervalBaseline, ProfilerSnapshot};
+use crate::report::ProfileReport;
+
+pub mod sink;
+pub use sink::{RotatingLog, Sink};
+
+#[cfg(test)]
+mod test;
+
+const DEFAULT_GRACE_NANOS: u64 = 50_000_000;
+
+/// Current interval of the reporter, which the profiled threads compare with their own.
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static PUBLISHED: Mutex<Vec<ProfilerSnapshot>> = Mutex::new(Vec::new());
+
+pub(crate) fn epoch() -> u64 {
+    EPOCH.load(Ordering::Relaxed)
+}
+
+pub(crate) fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed)
+}
+
+pub(crate) fn publish(snapshot: ProfilerSnapshot) {
+    published().push(snapshot);
+}
+
+fn published() -> MutexGuard<'static, Vec<ProfilerSnapshot>> {
+    PUBLISHED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The measurements of one thread over an interval.
+pub struct ThreadReport {
+    report: ProfileReport,
+}
+
+impl ThreadReport {
+    fn new(report: ProfileReport) -> Self {
+        Self { report }
+    }
+
+    /// Name of the thread, or its id if it is unnamed.
+    pub fn thread(&self) -> &str {
+        self.report.thread()
+    }
+
+    pub fn report(&self) -> &ProfileReport {
+        &self.report
+    }
+}
+
+/// The reports the profiled threads published in an interval.
+pub struct IntervalReport {
+    index: u64,
+    elapsed: Duration,
+    threads: Vec<ThreadReport>,
+}
+
+impl IntervalReport {
+    /// Position of the interval, from zero for the first one of the reporter.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Wall time of the interval.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn threads(&self) -> &[ThreadReport] {
+        &self.threads
+    }
+
+    /// Writes the report table of every thread to `writer`, under a header per interval.
+    ///
+    /// The tables are not coloured, since the reports are meant for files.
+    pub fn write(&self, mut writer: impl Write) -> Result<()> {
+        writeln!(
+            writer,
+            "\n# Interval {} ({} ms)",
+            self.index,
+            self.elapsed.as_millis()
+        )?;
+        for thread in &self.threads {
+            writeln!(writer, "\n## Thread {}", thread.thread())?;
+            thread.report.write_plain(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Configures a background reporter, which writes a report to a [`Sink`] every interval.
+///
+/// ```ignore
+/// let reporter = Reporter::new(Duration::from_nanos(10 * NANOS_PER_SEC), Sink::file(path)?)
+///     .spawn()?;
+/// // ...
+/// reporter.stop()?;
+/// ```
+pub struct Reporter {
+    interval: Duration,
+    grace: Duration,
+    sink: Sink,
+}
+
+impl Reporter {
+    pub fn new(interval: Duration, sink: Sink) -> Self {
+        Self {
+            interval,
+            grace: Duration::from_nanos(DEFAULT_GRACE_NANOS),
+            sink,
+        }
+    }
+
+    /// Sets how long the reporter waits for the threads to publish, after an interval ends.
+    pub fn grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Starts the reporter thread.
+    ///
+    /// Fails if another reporter is running. The first interval reports everything the
+    /// threads measured since their profilers were started or last reset.
+    pub fn spawn(self) -> Result<ReporterHandle> {
+        if RUNNING.swap(true, Ordering::AcqRel) {
+            return Err(Error::ReporterRunning);
+        }
+        // Drop what was published too late for the last reporter.
+        published().clear();
+        let (stop, stopped) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("tuff-reporter".to_string())
+            .spawn(move || {
+                let result = self.run(stopped);
+                RUNNING.store(false, Ordering::Release);
+                result
+            });
+        match thread {
+            Ok(thread) => Ok(ReporterHandle {
+                stop,
+                thread: Some(thread),
+            }),
+            Err(err) => {
+                RUNNING.store(false, Ordering::Release);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Reports every interval until stopped, and returns the first error of the sink.
+    fn run(mut self, stopped: mpsc::Receiver<()>) -> Result<()> {
+        let interval = std::time::Duration::from_nanos(self.interval.as_nanos());
+        let grace = std::time::Duration::from_nanos(self.grace.as_nanos());
+        let mut first_error = None;
+        let mut baselines: HashMap<ThreadId, IntervalBaseline> = HashMap::new();
+        let mut interval_start = Instant::now();
+        for index in 0.. {
+            let stopping = !matches!(
+                stopped.recv_timeout(interval),
+                Err(RecvTimeoutError::Timeout)
+            );
+            EPOCH.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(grace);
+
+            let now = Instant::now();
+            let snapshots = std::mem::take(&mut *published());
+            let threads = snapshots
+                .iter()
+                .map(|snapshot| {
+                    let baseline = baselines.entry(snapshot.thread_id()).or_default();
+                    ThreadReport::new(snapshot.interval_report(baseline))
+                })
+                .collect();
+            let report = IntervalReport {
+                index,
+                elapsed: now.duration_since(interval_start),
+                threads,
+            };
+            interval_start = now;
+            if let Err(err) = self.sink.write(&report) {
+                first_error.get_or_insert(err);
+            }
+            if stopping {
+                break;
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+/// A running background reporter, which is stopped when dropped.
+pub struct ReporterHandle {
+    stop: mpsc::Sender<()>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl ReporterHandle {
+    /// Reports the last interval, and stops the reporter.
+    ///
+    /// The calling thread publishes its measurements to the last interval, while the other
+    /// threads publish theirs if they close a block before it ends. Returns the first error
+    /// of the sink, if writing any of the reports failed.
+    pub fn stop(mut self) -> Result<()> {
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        Profiler::publish_global();
+        // The reporter may have already stopped, if its thread panicked.
+        let _ = self.stop.send(());
+        thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+impl Drop for ReporterHandle {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            let _ = self.join();
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::IntervalReport;
+use crate::error::Result;
+
+/// Where a background reporter writes the report of every interval.
+pub enum Sink {
+    /// Appends the reports to a file.
+    File(File),
+
+    /// Appends the reports to a file, which is rotated when it grows too large.
+    RotatingLog(RotatingLog),
+
+    /// Hands the reports to a function, on the reporter thread.
+    Callback(Box<dyn FnMut(&IntervalReport) + Send>),
+}
+
+impl Sink {
+    /// Appends the reports to the file at `path`, which is created if it does not exist.
+    pub fn file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::File(open_append(path.as_ref())?))
+    }
+
+    /// Appends the reports to the file at `path`, rotated as described in [`RotatingLog`].
+    pub fn rotating_log(
+        path: impl Into<PathBuf>,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Result<Self> {
+        Ok(Self::RotatingLog(RotatingLog::new(
+            path.into(),
+            max_bytes,
+            max_files,
+        )?))
+    }
+
+    pub fn callback(callback: impl FnMut(&IntervalReport) + Send + 'static) -> Self {
+        Self::Callback(Box::new(callback))
+    }
+
+    pub(crate) fn write(&mut self, report: &IntervalReport) -> Result<()> {
+        match self {
+            Self::File(file) => {
+                let mut buffer = Vec::new();
+                report.write(&mut buffer)?;
+                Ok(file.write_all(&buffer)?)
+            }
+            Self::RotatingLog(log) => log.write(report),
+            Self::Callback(callback) => {
+                callback(report);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A log file that is renamed to `path.1` when a report would grow it past `max_bytes`.
+///
+/// The older files are shifted to `path.2`, `path.3` and so on, and the files past
+/// `max_files`, including the current one, are removed. A report larger than `max_bytes`
+/// is still written whole, to a file of its own.
+pub struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingLog {
+    fn new(path: PathBuf, max_bytes: u64, max_files: usize) -> Result<Self> {
+        let file = open_append(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            len,
+            max_bytes,
+            max_files: max_files.max(1),
+        })
+    }
+
+    fn write(&mut self, report: &IntervalReport) -> Result<()> {
+        let mut buffer = Vec::new();
+        report.write(&mut buffer)?;
+        if self.len > 0 && self.len + buffer.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(&buffer)?;
+        self.len += buffer.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        let oldest = self.rotated_path(self.max_files - 1);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for i in (1..self.max_files - 1).rev() {
+            let from = self.rotated_path(i);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(i + 1))?;
+            }
+        }
+        if self.max_files > 1 {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Path of the `i`-th most recent rotated file; the current file is the zeroth.
+    fn rotated_path(&self, i: usize) -> PathBuf {
+        if i == 0 {
+            return self.path.clone();
+        }
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{i}"));
+        path.into()
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
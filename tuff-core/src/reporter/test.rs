@@ -0,0 +1,33 @@
+use std::fs;
+
+use super::{IntervalReport, Sink};
+use crate::metrics::Duration;
+
+fn interval(index: u64) -> IntervalReport {
+    IntervalReport {
+        index,
+        elapsed: Duration::from_nanos(0),
+        threads: Vec::new(),
+    }
+}
+
+#[test]
+fn test_rotating_log() {
+    let dir = std::env::temp_dir().join(format!("tuff-rotating-log-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("profile.log");
+
+    // Each report without threads is a 20 byte header.
+    let mut sink = Sink::rotating_log(&path, 50, 2).unwrap();
+    for index in 0..5 {
+        sink.write(&interval(index)).unwrap();
+    }
+    let current = fs::read_to_string(&path).unwrap();
+    let rotated = fs::read_to_string(dir.join("profile.log.1")).unwrap();
+    let oldest_exists = dir.join("profile.log.2").exists();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(current, "\n# Interval 4 (0 ms)\n");
+    assert_eq!(rotated, "\n# Interval 2 (0 ms)\n\n# Interval 3 (0 ms)\n");
+    assert!(!oldest_exists);
+}
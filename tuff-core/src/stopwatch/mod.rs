@@ -0,0 +1,121 @@
+use std::marker::PhantomData;
+
+use crate::metrics::{Duration, TimeSource};
+use crate::os::OsClock;
+
+/// A stopwatch that measures elapsed time with a [`TimeSource`].
+///
+/// The stopwatch runs from creation, and can be paused and resumed; the time while paused
+/// is not counted. Laps split the time counted so far.
+///
+/// ```ignore
+/// let mut sw = Stopwatch::start();
+/// parse();
+/// let parse_time = sw.lap();
+/// sw.pause();
+/// idle();
+/// sw.resume();
+/// let total = sw.elapsed();
+///
+/// let cycles = Stopwatch::<CpuCounter>::new();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stopwatch<S: TimeSource = OsClock> {
+    /// Ticks counted before the stopwatch was last resumed.
+    accumulated: u64,
+
+    /// Value of the source when the stopwatch was last resumed, if it is running.
+    resumed_at: Option<u64>,
+
+    /// Ticks counted when the last lap ended.
+    lap_end: u64,
+    laps: Vec<u64>,
+    _source: PhantomData<S>,
+}
+
+impl Stopwatch {
+    /// Starts a stopwatch on the OS clock.
+    pub fn start() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: TimeSource> Stopwatch<S> {
+    /// Starts a stopwatch on the source `S`.
+    pub fn new() -> Self {
+        Self {
+            accumulated: 0,
+            resumed_at: Some(S::read()),
+            lap_end: 0,
+            laps: Vec::new(),
+            _source: PhantomData,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.resumed_at.is_some()
+    }
+
+    /// Stops counting time, until the stopwatch is resumed.
+    pub fn pause(&mut self) {
+        if let Some(resumed_at) = self.resumed_at.take() {
+            self.accumulated += S::read() - resumed_at;
+        }
+    }
+
+    /// Counts time again after a pause.
+    pub fn resume(&mut self) {
+        if self.resumed_at.is_none() {
+            self.resumed_at = Some(S::read());
+        }
+    }
+
+    /// Clears the time counted and the laps, and starts the stopwatch again.
+    pub fn restart(&mut self) {
+        self.accumulated = 0;
+        self.lap_end = 0;
+        self.laps.clear();
+        self.resumed_at = Some(S::read());
+    }
+
+    /// Returns the ticks of the source counted so far.
+    pub fn elapsed_ticks(&self) -> u64 {
+        match self.resumed_at {
+            Some(resumed_at) => self.accumulated + (S::read() - resumed_at),
+            None => self.accumulated,
+        }
+    }
+
+    /// Returns the time counted so far.
+    pub fn elapsed(&self) -> Duration {
+        S::to_duration(self.elapsed_ticks())
+    }
+
+    /// Ends a lap, and returns the time counted since the previous one.
+    pub fn lap(&mut self) -> Duration {
+        let elapsed = self.elapsed_ticks();
+        let lap = elapsed - self.lap_end;
+        self.lap_end = elapsed;
+        self.laps.push(lap);
+        S::to_duration(lap)
+    }
+
+    /// Returns the ticks of the source counted in each lap, in order.
+    pub fn lap_ticks(&self) -> &[u64] {
+        &self.laps
+    }
+
+    /// Returns the time counted in each lap, in order.
+    pub fn laps(&self) -> Vec<Duration> {
+        self.laps.iter().map(|&lap| S::to_duration(lap)).collect()
+    }
+}
+
+impl<S: TimeSource> Default for Stopwatch<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,58 @@
+use std::thread::sleep;
+
+use super::Stopwatch;
+use crate::arch::{CpuCounter, CpuCounterSerialized};
+use crate::metrics::TimeSource;
+use crate::os::OsClock;
+
+const TEN_MILLIS: std::time::Duration = std::time::Duration::from_millis(10);
+const TEN_MILLIS_NANOS: u64 = 10_000_000;
+
+#[test]
+fn test_stopwatch_pause_and_resume() {
+    let mut sw = Stopwatch::start();
+    sleep(TEN_MILLIS);
+    sw.pause();
+    assert!(!sw.is_running());
+    let paused = sw.elapsed();
+    sleep(TEN_MILLIS);
+    assert_eq!(sw.elapsed(), paused);
+
+    sw.resume();
+    sleep(TEN_MILLIS);
+    let elapsed = sw.elapsed().as_nanos();
+    assert!(elapsed >= 2 * TEN_MILLIS_NANOS);
+    assert!(elapsed < 3 * TEN_MILLIS_NANOS + paused.as_nanos());
+}
+
+#[test]
+fn test_stopwatch_laps() {
+    let mut sw = Stopwatch::<OsClock>::new();
+    sleep(TEN_MILLIS);
+    let first = sw.lap();
+    sleep(TEN_MILLIS);
+    let second = sw.lap();
+    assert!(first.as_nanos() >= TEN_MILLIS_NANOS);
+    assert!(second.as_nanos() >= TEN_MILLIS_NANOS);
+    assert_eq!(sw.laps(), [first, second]);
+    assert!(sw.elapsed() >= first + second);
+
+    sw.restart();
+    assert!(sw.laps().is_empty());
+    assert!(sw.elapsed() < first);
+}
+
+#[test]
+fn test_cpu_counter_sources() {
+    fn measure<S: TimeSource>() -> u64 {
+        let sw = Stopwatch::<S>::new();
+        sleep(TEN_MILLIS);
+        sw.elapsed().as_nanos()
+    }
+    assert!(measure::<CpuCounter>() > 0);
+    assert!(measure::<CpuCounterSerialized>() > 0);
+    assert_eq!(
+        OsClock::to_duration(TEN_MILLIS_NANOS).as_nanos(),
+        TEN_MILLIS_NANOS
+    );
+}
@@ -0,0 +1,139 @@
+//! Integration with the `tracing` crate, in both directions.
+//!
+//! [`ProfileLayer`] profiles the spans of an application that is already instrumented with
+//! `tracing`, and [`emit_spans`] makes the profile blocks enter a `tracing` span, so that
+//! they show up in the application's traces.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use ::tracing::callsite::Identifier;
+use ::tracing::span::Id;
+use ::tracing::{Level, Span, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::profile::{ProfileBlock, Profiler};
+
+#[cfg(test)]
+mod test;
+
+/// Target of the spans that the profile blocks enter, which [`ProfileLayer`] ignores.
+pub const SPAN_TARGET: &str = "tuff";
+
+/// Level of the spans that the profile blocks enter, or zero if they enter none.
+static SPAN_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    /// Blocks of the spans the thread is in, innermost last.
+    static ENTERED: RefCell<Vec<(Id, ProfileBlock)>> = const { RefCell::new(Vec::new()) };
+
+    /// Anchors of the span call-sites, in the thread's profiler.
+    static ANCHORS: RefCell<HashMap<Identifier, usize>> = RefCell::new(HashMap::new());
+}
+
+/// A layer that opens a profile block while a span is entered, on the thread's profiler.
+///
+/// The blocks are labelled by the name of the span, and get an anchor per span call-site.
+/// A span entered several times, such as the span of a future, records a hit every time.
+/// The profiler of each thread must be started for its spans to be timed.
+///
+/// ```ignore
+/// tracing_subscriber::registry()
+///     .with(tuff::tracing::ProfileLayer)
+///     .init();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProfileLayer;
+
+impl<S> Layer<S> for ProfileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let metadata = span.metadata();
+        if metadata.target() == SPAN_TARGET {
+            return;
+        }
+        // Spans declared on the same line have different call-sites, unlike profile blocks.
+        let anchor_index = ANCHORS.with(|anchors| {
+            *anchors
+                .borrow_mut()
+                .entry(metadata.callsite())
+                .or_insert_with(Profiler::new_anchor)
+        });
+        let block = ProfileBlock::new(metadata.name(), anchor_index);
+        ENTERED.with(|entered| entered.borrow_mut().push((id.clone(), block)));
+    }
+
+    fn on_exit(&self, id: &Id, _ctx: Context<'_, S>) {
+        // Spans exited out of order close the blocks entered after them, since the blocks
+        // of a thread must be closed in the reverse order they were opened.
+        let closed = ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            let position = entered.iter().rposition(|(entered, _)| entered == id)?;
+            Some(entered.split_off(position))
+        });
+        // The blocks are dropped innermost first.
+        if let Some(closed) = closed {
+            closed.into_iter().rev().for_each(drop);
+        }
+    }
+}
+
+/// Sets the level of the `tracing` spans that the profile blocks enter, or `None` for the
+/// blocks not to enter spans, which is the default.
+///
+/// The spans have the target [`SPAN_TARGET`], the name `profile_block`, and the label of
+/// the block as the `label` field.
+pub fn emit_spans(level: Option<Level>) {
+    let level = level.map_or(0, |level| match level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        _ => 5,
+    });
+    SPAN_LEVEL.store(level, Ordering::Relaxed);
+}
+
+/// The span of a profile block, entered while the block is open.
+///
+/// The span is entered through its subscriber, rather than with an `Entered` guard, so
+/// that the profile blocks stay `Send`.
+#[derive(Debug)]
+pub(crate) struct BlockSpan(Span);
+
+impl BlockSpan {
+    /// Enters the span of a profile block, if spans are emitted.
+    #[inline(always)]
+    pub(crate) fn enter(label: &'static str) -> Option<Self> {
+        match SPAN_LEVEL.load(Ordering::Relaxed) {
+            0 => None,
+            level => Some(Self::enter_at(level, label)),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn enter_at(level: u8, label: &'static str) -> Self {
+        let span = match level {
+            1 => ::tracing::error_span!(target: SPAN_TARGET, "profile_block", label),
+            2 => ::tracing::warn_span!(target: SPAN_TARGET, "profile_block", label),
+            3 => ::tracing::info_span!(target: SPAN_TARGET, "profile_block", label),
+            4 => ::tracing::debug_span!(target: SPAN_TARGET, "profile_block", label),
+            _ => ::tracing::trace_span!(target: SPAN_TARGET, "profile_block", label),
+        };
+        span.with_subscriber(|(id, dispatch)| dispatch.enter(id));
+        Self(span)
+    }
+}
+
+impl Drop for BlockSpan {
+    fn drop(&mut self) {
+        self.0.with_subscriber(|(id, dispatch)| dispatch.exit(id));
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::{Arc, Mutex};
+
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::Registry;
+
+use super::{ProfileLayer, SPAN_TARGET, emit_spans};
+use crate::metrics::MockClock;
+use crate::{ProfileBlock, Profiler};
+
+#[test]
+fn test_layer_profiles_spans() {
+    let subscriber = Registry::default().with(ProfileLayer);
+    ::tracing::subscriber::with_default(subscriber, || {
+        MockClock::set(0);
+        Profiler::start_global_with::<MockClock>();
+        {
+            let _outer = ::tracing::info_span!("outer").entered();
+            MockClock::advance(10);
+            for elapsed in [20, 30] {
+                let _inner = ::tracing::info_span!("inner").entered();
+                MockClock::advance(elapsed);
+            }
+        }
+        // Entering a span again records another hit.
+        let span = ::tracing::info_span!("reentered");
+        for _ in 0..3 {
+            let _entered = span.enter();
+            MockClock::advance(5);
+        }
+        Profiler::stop_global();
+    });
+
+    let report = Profiler::report().unwrap();
+    let values = |label: &str| {
+        let meas = report
+            .measurements()
+            .iter()
+            .find(|m| m.label() == label)
+            .unwrap();
+        (
+            meas.hit_count(),
+            meas.elapsed_exclusive.value(),
+            meas.elapsed_inclusive.value(),
+        )
+    };
+    assert_eq!(values("outer"), (1, 10, 60));
+    assert_eq!(values("inner"), (2, 50, 50));
+    assert_eq!(values("reentered"), (3, 15, 15));
+}
+
+#[test]
+fn test_layer_separates_spans_on_the_same_line() {
+    let subscriber = Registry::default().with(ProfileLayer);
+    ::tracing::subscriber::with_default(subscriber, || {
+        Profiler::start_global_with::<MockClock>();
+        #[rustfmt::skip]
+        let (first, second) = (::tracing::info_span!("first"), ::tracing::info_span!("second"));
+        drop(first.enter());
+        drop(second.enter());
+        drop(second.enter());
+        Profiler::stop_global();
+    });
+
+    let report = Profiler::report().unwrap();
+    let hits = |label: &str| {
+        report
+            .measurements()
+            .iter()
+            .find(|m| m.label() == label)
+            .unwrap()
+            .hit_count()
+    };
+    assert_eq!(hits("first"), 1);
+    assert_eq!(hits("second"), 2);
+}
+
+/// Records the labels of the spans entered by the profile blocks.
+struct LabelRecorder(Arc<Mutex<Vec<String>>>);
+
+impl<S: ::tracing::Subscriber> Layer<S> for LabelRecorder {
+    fn on_new_span(
+        &self,
+        attrs: &::tracing::span::Attributes<'_>,
+        _id: &::tracing::span::Id,
+        _ctx: Context<'_, S>,
+    ) {
+        if attrs.metadata().target() == SPAN_TARGET {
+            let mut label = String::new();
+            attrs.record(
+                &mut |_: &::tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    label = format!("{value:?}");
+                },
+            );
+            self.0.lock().unwrap().push(label);
+        }
+    }
+}
+
+#[test]
+fn test_blocks_emit_spans() {
+    let labels = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = Registry::default().with(LabelRecorder(labels.clone()));
+    ::tracing::subscriber::with_default(subscriber, || {
+        emit_spans(Some(::tracing::Level::INFO));
+        {
+            let _outer = ProfileBlock::new("outer", 1);
+            let _inner = ProfileBlock::new("inner", 2);
+        }
+        emit_spans(None);
+        let _ignored = ProfileBlock::new("ignored", 3);
+    });
+    assert_eq!(*labels.lock().unwrap(), ["\"outer\"", "\"inner\""]);
+}
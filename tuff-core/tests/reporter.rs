@@ -0,0 +1,92 @@
+//! The reporter is global to the process, so it is tested apart from the profiler's tests.
+#![cfg(feature = "reporter")]
+use std::sync::mpsc;
+
+use tuff_core::metrics::{Duration, MetricType};
+use tuff_core::reporter::{IntervalReport, Sink};
+use tuff_core::{Error, ProfileBlock, Profiler, Reporter};
+
+/// Returns the hits of `label` in the next interval this thread published to.
+fn next_hits(reports: &mpsc::Receiver<Vec<(String, u64)>>, label: &str) -> u64 {
+    let thread = std::thread::current().name().unwrap().to_string();
+    loop {
+        // Keep closing blocks, which publishes the measurements after an interval ends.
+        drop(ProfileBlock::new("poll", 2));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        for threads in reports.try_iter() {
+            for (name, hits) in threads {
+                if name == format!("{thread}/{label}") {
+                    return hits;
+                }
+            }
+        }
+    }
+}
+
+fn hits_by_thread(report: &IntervalReport) -> Vec<(String, u64)> {
+    report
+        .threads()
+        .iter()
+        .flat_map(|thread| {
+            thread.report().measurements().iter().map(|meas| {
+                (
+                    format!("{}/{}", thread.thread(), meas.label()),
+                    meas.hit_count(),
+                )
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_reporter_publishes_interval_deltas() {
+    let (sender, reports) = mpsc::channel();
+    let reporter = Reporter::new(
+        Duration::from_nanos(20_000_000),
+        Sink::callback(move |report| {
+            let _ = sender.send(hits_by_thread(report));
+        }),
+    )
+    .grace(Duration::from_nanos(5_000_000))
+    .spawn()
+    .unwrap();
+    let callback = Sink::callback(|_| {});
+    assert!(matches!(
+        Reporter::new(Duration::from_nanos(1), callback).spawn(),
+        Err(Error::ReporterRunning)
+    ));
+
+    Profiler::start_global(MetricType::OsClock);
+    for _ in 0..4 {
+        drop(ProfileBlock::new("work", 1));
+    }
+    assert_eq!(next_hits(&reports, "work"), 4);
+    for _ in 0..3 {
+        drop(ProfileBlock::new("work", 1));
+    }
+    assert_eq!(next_hits(&reports, "work"), 3);
+    for _ in 0..2 {
+        drop(ProfileBlock::new("work", 1));
+    }
+    Profiler::stop_global();
+
+    // Stopping publishes the last hits of the thread, without another block closed.
+    reporter.stop().unwrap();
+    let thread = std::thread::current().name().unwrap().to_string();
+    let last_hits: u64 = reports
+        .iter()
+        .flatten()
+        .filter(|(name, _)| *name == format!("{thread}/work"))
+        .map(|(_, hits)| hits)
+        .sum();
+    assert_eq!(last_hits, 2);
+
+    // Publishing the intervals leaves the measurements of the thread as they were.
+    let report = Profiler::report().unwrap();
+    let work = report
+        .measurements()
+        .iter()
+        .find(|meas| meas.label() == "work")
+        .unwrap();
+    assert_eq!(work.hit_count(), 9);
+}
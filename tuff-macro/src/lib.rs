@@ -1,25 +1,429 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{ItemFn, parse_macro_input};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    AttrStyle, Attribute, Block, Expr, Ident, ImplItem, Item, ItemFn, ItemImpl, ItemMod, LitStr,
+    Signature, Token, Type, Visibility, parse_macro_input,
+};
+
+/// Arguments of the `profile_fn` attribute.
+///
+/// Accepts, in any order and separated by commas, a string literal label, a positional
+/// anchor index, and the named arguments `label = "..."`, `index = <expr>` and
+/// `bytes = <expr>`.
+struct ProfileFnArgs {
+    /// Label of the block, defaults to the path of the function.
+    label: Option<LitStr>,
+
+    /// Index of the profile anchor, defaults to one assigned to the call-site.
+    index: Option<Expr>,
+
+    /// Number of bytes processed by a call, to report the throughput of the function.
+    bytes: Option<Expr>,
+}
+
+impl ProfileFnArgs {
+    fn set<T>(
+        slot: &mut Option<T>,
+        value: T,
+        name: &str,
+        span: proc_macro2::Span,
+    ) -> syn::Result<()> {
+        if slot.replace(value).is_some() {
+            return Err(syn::Error::new(
+                span,
+                format!("duplicate `{name}` argument"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Parse for ProfileFnArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self {
+            label: None,
+            index: None,
+            bytes: None,
+        };
+        while !input.is_empty() {
+            let span = input.span();
+            if input.peek(LitStr) {
+                Self::set(&mut args.label, input.parse()?, "label", span)?;
+            } else if input.peek(Ident) && input.peek2(Token![=]) {
+                let name: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                match name.to_string().as_str() {
+                    "label" => Self::set(&mut args.label, input.parse()?, "label", span)?,
+                    "index" => Self::set(&mut args.index, input.parse()?, "index", span)?,
+                    "bytes" => Self::set(&mut args.bytes, input.parse()?, "bytes", span)?,
+                    _ => {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            "unknown argument, expected `label`, `index` or `bytes`",
+                        ));
+                    }
+                }
+            } else {
+                Self::set(&mut args.index, input.parse()?, "index", span)?;
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
 
 #[proc_macro_attribute]
-pub fn profile_fn(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn profile_fn(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as ProfileFnArgs);
     let fn_item = parse_macro_input!(item as ItemFn);
-    let fn_sig = &fn_item.sig;
-    let fn_block_stmts = &fn_item.block.stmts;
+    // A function in an `impl` block is parsed the same as a free one, so it is only known
+    // to be in one from its signature.
+    let in_impl = fn_item.sig.receiver().is_some() || names_self(fn_item.sig.to_token_stream());
+    instrument_fn(
+        &args,
+        in_impl,
+        &fn_item.attrs,
+        &fn_item.vis,
+        None,
+        &fn_item.sig,
+        &fn_item.block,
+    )
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Instruments every method of an `impl` block, labelling each block `Type::method`.
+///
+/// Accepts `include(...)` and `exclude(...)` lists of method names. `const fn`s and
+/// methods that already have a `profile_fn` attribute are left unchanged.
+#[proc_macro_attribute]
+pub fn profile_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut item_impl = parse_macro_input!(item as ItemImpl);
+    let result = syn::parse::<ProfileItemsArgs>(attr).and_then(|args| {
+        instrument_impl(&args, &mut item_impl)?;
+        args.check_all_found()
+    });
+    with_error(result, item_impl)
+}
+
+/// Instruments every function, and every method of the `impl` blocks, of an inline module.
+///
+/// Functions are labelled by their name and methods `Type::method`. Accepts the same
+/// `include(...)` and `exclude(...)` lists as `profile_impl`.
+#[proc_macro_attribute]
+pub fn profile_mod(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut item_mod = parse_macro_input!(item as ItemMod);
+    let result = syn::parse::<ProfileItemsArgs>(attr).and_then(|args| {
+        instrument_mod(&args, &mut item_mod)?;
+        args.check_all_found()
+    });
+    with_error(result, item_mod)
+}
+
+/// Emits the item along with the error, if any, so that the error is the only one reported.
+fn with_error(result: syn::Result<()>, item: impl ToTokens) -> TokenStream {
+    let error = result.err().map(syn::Error::into_compile_error);
+    quote! { #error #item }.into()
+}
+
+/// Arguments of the `profile_impl` and `profile_mod` attributes.
+///
+/// Accepts `include(a, b, ...)`, to only instrument the functions named, and
+/// `exclude(a, b, ...)`, to leave the functions named unchanged.
+struct ProfileItemsArgs {
+    include: Option<Vec<Ident>>,
+    exclude: Vec<Ident>,
+
+    /// Names of the listed functions that were found, to report the unknown ones.
+    found: RefCell<HashSet<String>>,
+}
+
+impl ProfileItemsArgs {
+    /// Whether the function named `ident` is instrumented.
+    fn selects(&self, ident: &Ident) -> bool {
+        let listed = |names: &[Ident]| names.iter().any(|name| name == ident);
+        let included = self.include.as_deref().is_none_or(listed);
+        let excluded = listed(&self.exclude);
+        if included && self.include.is_some() || excluded {
+            self.found.borrow_mut().insert(ident.to_string());
+        }
+        included && !excluded
+    }
+
+    /// Fails on the listed names that do not match any function.
+    fn check_all_found(&self) -> syn::Result<()> {
+        let found = self.found.borrow();
+        let unknown = self
+            .include
+            .iter()
+            .flatten()
+            .chain(&self.exclude)
+            .find(|name| !found.contains(&name.to_string()));
+        match unknown {
+            Some(name) => Err(syn::Error::new(
+                name.span(),
+                format!("no function named `{name}`"),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Parse for ProfileItemsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut include = None;
+        let mut exclude = None;
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            let content;
+            syn::parenthesized!(content in input);
+            let names = content
+                .parse_terminated(Ident::parse, Token![,])?
+                .into_iter()
+                .collect();
+            match name.to_string().as_str() {
+                "include" => ProfileFnArgs::set(&mut include, names, "include", name.span())?,
+                "exclude" => ProfileFnArgs::set(&mut exclude, names, "exclude", name.span())?,
+                _ => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "unknown argument, expected `include` or `exclude`",
+                    ));
+                }
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self {
+            include,
+            exclude: exclude.unwrap_or_default(),
+            found: RefCell::new(HashSet::new()),
+        })
+    }
+}
+
+/// Whether the function can be instrumented without being asked to explicitly.
+fn is_instrumentable(attrs: &[Attribute], sig: &Signature) -> bool {
+    let has_profile_fn = attrs.iter().any(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "profile_fn")
+    });
+    sig.constness.is_none() && !has_profile_fn
+}
+
+fn instrument_impl(args: &ProfileItemsArgs, item_impl: &mut ItemImpl) -> syn::Result<()> {
+    let type_name = match &*item_impl.self_ty {
+        Type::Path(type_path) if type_path.qself.is_none() => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    };
+    let Some(type_name) = type_name else {
+        return Err(syn::Error::new_spanned(
+            &item_impl.self_ty,
+            "`profile_impl` requires the implemented type to be a path",
+        ));
+    };
+
+    for item in &mut item_impl.items {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+        if !args.selects(&method.sig.ident) || !is_instrumentable(&method.attrs, &method.sig) {
+            continue;
+        }
+        let label = format!("{type_name}::{}", method.sig.ident);
+        let fn_args = ProfileFnArgs {
+            label: Some(LitStr::new(&label, method.sig.ident.span())),
+            index: None,
+            bytes: None,
+        };
+        let instrumented = instrument_fn(
+            &fn_args,
+            true,
+            &method.attrs,
+            &method.vis,
+            method.defaultness.as_ref(),
+            &method.sig,
+            &method.block,
+        )?;
+        *item = ImplItem::Verbatim(instrumented);
+    }
+    Ok(())
+}
+
+fn instrument_mod(args: &ProfileItemsArgs, item_mod: &mut ItemMod) -> syn::Result<()> {
+    let Some((_, items)) = &mut item_mod.content else {
+        return Err(syn::Error::new_spanned(
+            &item_mod.ident,
+            "`profile_mod` requires an inline module",
+        ));
+    };
+
+    let fn_args = ProfileFnArgs {
+        label: None,
+        index: None,
+        bytes: None,
+    };
+    for item in items {
+        match item {
+            Item::Fn(function) => {
+                if !args.selects(&function.sig.ident)
+                    || !is_instrumentable(&function.attrs, &function.sig)
+                {
+                    continue;
+                }
+                let instrumented = instrument_fn(
+                    &fn_args,
+                    false,
+                    &function.attrs,
+                    &function.vis,
+                    None,
+                    &function.sig,
+                    &function.block,
+                )?;
+                *item = Item::Verbatim(instrumented);
+            }
+            Item::Impl(item_impl) => instrument_impl(args, item_impl)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Checks whether the tokens name `Self`, which only the functions of an `impl` block or
+/// a trait can.
+fn names_self(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "Self",
+        TokenTree::Group(group) => names_self(group.stream()),
+        _ => false,
+    })
+}
+
+/// Expands a function into one that opens a profile block around its body.
+///
+/// The default label of a function in an `impl` block, `in_impl`, is preceded by its type.
+fn instrument_fn(
+    args: &ProfileFnArgs,
+    in_impl: bool,
+    attrs: &[Attribute],
+    fn_vis: &Visibility,
+    defaultness: Option<&Token![default]>,
+    fn_sig: &Signature,
+    fn_block: &Block,
+) -> syn::Result<TokenStream2> {
+    let fn_block_stmts = &fn_block.stmts;
+
+    // A block can not be opened in a constant context.
+    if let Some(constness) = &fn_sig.constness {
+        return Err(syn::Error::new_spanned(
+            constness,
+            "`profile_fn` can not instrument a `const fn`",
+        ));
+    }
+
+    // Inner attributes, such as `#![allow(...)]`, are parsed into the attributes of the
+    // function and must go back into its body.
+    let (fn_outer_attrs, fn_inner_attrs): (Vec<_>, Vec<_>) = attrs
+        .iter()
+        .partition(|attr| matches!(attr.style, AttrStyle::Outer));
+
+    // The call-site is located at the name of the function, so that the functions
+    // instrumented by a single `profile_impl` or `profile_mod` get different anchors.
+    let call_site = quote_spanned! { fn_sig.ident.span()=>
+        tuff_core::CallSite::new(file!(), line!(), column!())
+    };
+
+    let index = match &args.index {
+        Some(index) => quote! { #index },
+        None => quote! {
+            {
+                const __CALL_SITE: tuff_core::CallSite = #call_site;
+                tuff_core::Profiler::get_or_insert(__CALL_SITE)
+            }
+        },
+    };
+
+    // The path of the function is taken from the type name of an item nested in it, and
+    // computed only once.
+    let label_fn = if in_impl {
+        quote! { method_label }
+    } else {
+        quote! { function_label }
+    };
+    let label = match &args.label {
+        Some(label) => quote! { #label },
+        None => quote! {
+            {
+                fn __tuff_fn() {}
+                fn __tuff_type_name<T>(_: T) -> &'static str {
+                    ::std::any::type_name::<T>()
+                }
+                static __LABEL: ::std::sync::OnceLock<&'static str> = ::std::sync::OnceLock::new();
+                *__LABEL.get_or_init(|| {
+                    tuff_core::profile::#label_fn(__tuff_type_name(__tuff_fn))
+                })
+            }
+        },
+    };
+
+    let bytes = match &args.bytes {
+        Some(bytes) => quote! { (#bytes) as u64 },
+        None => quote! { 0 },
+    };
+
+    // The body of an async function runs over several polls, possibly on different
+    // threads, so it is wrapped in a future that profiles each poll instead.
+    if fn_sig.asyncness.is_some() {
+        let instrumented = match &args.index {
+            Some(index) => quote! {
+                tuff_core::Instrumented::new(__future, #label, #index)
+            },
+            None => quote! {
+                tuff_core::Instrumented::at_call_site(
+                    __future,
+                    #label,
+                    #call_site,
+                )
+            },
+        };
+        let expanded = quote! {
+            #(#fn_outer_attrs)*
+            #fn_vis #defaultness #fn_sig {
+                #(#fn_inner_attrs)*
+                use tuff_core;
+                let __bytes = #bytes;
+                let __future = async move { #(#fn_block_stmts)* };
+                #instrumented.bytes(__bytes).await
+            }
+        };
+        return Ok(expanded);
+    }
 
     let expanded = quote! {
-        #fn_sig {
+        #(#fn_outer_attrs)*
+        #fn_vis #defaultness #fn_sig {
+            #(#fn_inner_attrs)*
             use tuff_core;
-            let __idx = {
-                const __CALL_SITE: tuff_core::CallSite = tuff_core::CallSite::new(file!(), line!(), column!());
-                tuff_core::Profiler::get_or_insert(__CALL_SITE)
-            };
-            let __block = tuff_core::ProfileBlock::new("", __idx);
+            let __idx = #index;
+            let __block = tuff_core::ProfileBlock::with_bytes(#label, __idx, #bytes);
 
             #(#fn_block_stmts)*
         }
     };
 
-    expanded.into()
+    Ok(expanded)
 }
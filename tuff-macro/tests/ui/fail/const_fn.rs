@@ -0,0 +1,10 @@
+use tuff_macro::profile_fn;
+
+#[profile_fn]
+const fn square(x: u32) -> u32 {
+    x * x
+}
+
+fn main() {
+    square(2);
+}
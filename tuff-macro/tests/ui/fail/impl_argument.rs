@@ -0,0 +1,14 @@
+use tuff_macro::profile_impl;
+
+struct Square(u32);
+
+#[profile_impl(only(area))]
+impl Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+fn main() {
+    Square(2).area();
+}
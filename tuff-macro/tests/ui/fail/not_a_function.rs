@@ -0,0 +1,6 @@
+use tuff_macro::profile_fn;
+
+#[profile_fn]
+struct Square(u32);
+
+fn main() {}
@@ -0,0 +1,50 @@
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use tuff_macro::profile_fn;
+
+#[profile_fn]
+async fn double(value: u32) -> u32 {
+    value * 2
+}
+
+#[profile_fn("parse", bytes = input.len())]
+async fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+    if input.is_empty() {
+        return Ok(0);
+    }
+    let value = input.parse::<u32>()?;
+    Ok(double(value).await)
+}
+
+struct Service {
+    base: u32,
+}
+
+impl Service {
+    #[profile_fn]
+    pub async fn call<'a>(&'a self, input: &'a str) -> u32 {
+        self.base + parse(input).await.unwrap_or(0)
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+fn main() {
+    let service = Service { base: 1 };
+    assert_eq!(block_on(assert_send(service.call("20"))), 41);
+    assert_eq!(block_on(parse("")), Ok(0));
+}
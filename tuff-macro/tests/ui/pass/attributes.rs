@@ -0,0 +1,28 @@
+#![deny(unused_must_use)]
+use tuff_macro::profile_fn;
+
+/// Documentation is kept.
+#[profile_fn]
+#[must_use]
+#[inline]
+fn answer() -> u32 {
+    42
+}
+
+#[profile_fn]
+#[cfg(any())]
+fn compiled_out() -> u32 {
+    does_not_exist()
+}
+
+#[profile_fn]
+#[allow(unused_variables)]
+fn unused(value: u32) {
+    #![allow(clippy::let_unit_value)]
+    let unit = ();
+}
+
+fn main() {
+    let value = answer();
+    unused(value);
+}
@@ -0,0 +1,16 @@
+use tuff_macro::profile_fn;
+
+#[profile_fn(bytes = input.len())]
+fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+    if input.is_empty() {
+        return Ok(0);
+    }
+    let value = input.parse::<u32>()?;
+    Ok(value)
+}
+
+fn main() {
+    assert_eq!(parse(""), Ok(0));
+    assert_eq!(parse("12"), Ok(12));
+    assert!(parse("x").is_err());
+}
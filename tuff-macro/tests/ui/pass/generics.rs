@@ -0,0 +1,35 @@
+use std::fmt::Debug;
+use std::ops::Add;
+
+use tuff_macro::profile_fn;
+
+#[profile_fn]
+fn sum<T: Add<Output = T> + Default + Copy>(values: &[T]) -> T {
+    values.iter().fold(T::default(), |acc, value| acc + *value)
+}
+
+#[profile_fn]
+fn describe<T>(value: T) -> String
+where
+    T: Debug,
+{
+    format!("{value:?}")
+}
+
+#[profile_fn]
+fn first<const N: usize>(values: [u8; N]) -> Option<u8> {
+    values.first().copied()
+}
+
+#[profile_fn]
+fn apply(f: impl Fn(u32) -> u32, value: u32) -> u32 {
+    f(value)
+}
+
+fn main() {
+    assert_eq!(sum(&[1, 2, 3]), 6);
+    assert_eq!(sum(&[1.5, 2.5]), 4.0);
+    assert_eq!(describe("tuff"), "\"tuff\"");
+    assert_eq!(first([7, 8]), Some(7));
+    assert_eq!(apply(|x| x * 2, 4), 8);
+}
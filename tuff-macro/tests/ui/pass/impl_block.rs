@@ -0,0 +1,73 @@
+use tuff_core::Profiler;
+use tuff_core::metrics::MetricType;
+use tuff_macro::{profile_fn, profile_impl};
+
+struct Buffer<T> {
+    values: Vec<T>,
+}
+
+#[profile_impl(exclude(len))]
+impl<T: Clone> Buffer<T> {
+    const CAPACITY: usize = 4;
+
+    pub const fn capacity() -> usize {
+        Self::CAPACITY
+    }
+
+    pub fn new() -> Self {
+        Self {
+            values: Vec::with_capacity(Self::CAPACITY),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.values.push(value);
+    }
+
+    #[profile_fn("custom")]
+    pub fn first(&self) -> Option<T> {
+        self.values.first().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub async fn last(&self) -> Option<T> {
+        self.values.last().cloned()
+    }
+}
+
+trait Shape {
+    fn area(&self) -> u32;
+    fn name(&self) -> &'static str;
+}
+
+struct Square(u32);
+
+#[profile_impl(include(area))]
+impl Shape for Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+
+    fn name(&self) -> &'static str {
+        "square"
+    }
+}
+
+fn main() {
+    Profiler::start_global(MetricType::OsClock);
+    let mut buffer = Buffer::new();
+    buffer.push(Square(2).area());
+    assert_eq!(buffer.first(), Some(4));
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(Buffer::<u32>::capacity(), 4);
+    assert_eq!(Square(3).name(), "square");
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let mut labels: Vec<_> = report.measurements().iter().map(|m| m.label()).collect();
+    labels.sort();
+    assert_eq!(labels, ["Buffer::new", "Buffer::push", "Square::area", "custom"]);
+}
@@ -0,0 +1,32 @@
+use tuff_macro::profile_fn;
+
+#[profile_fn]
+fn longest<'a>(a: &'a str, b: &'a str) -> &'a str {
+    if a.len() >= b.len() { a } else { b }
+}
+
+#[profile_fn]
+fn split_first<'a, 'b: 'a>(words: &'a [&'b str]) -> Option<&'b str>
+where
+    'b: 'a,
+{
+    words.first().copied()
+}
+
+struct Parser<'s> {
+    input: &'s str,
+}
+
+impl<'s> Parser<'s> {
+    #[profile_fn]
+    fn rest(&self, at: usize) -> &'s str {
+        &self.input[at..]
+    }
+}
+
+fn main() {
+    assert_eq!(longest("ab", "c"), "ab");
+    assert_eq!(split_first(&["x", "y"]), Some("x"));
+    let parser = Parser { input: "profile" };
+    assert_eq!(parser.rest(3), "file");
+}
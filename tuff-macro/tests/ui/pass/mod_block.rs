@@ -0,0 +1,38 @@
+use tuff_core::Profiler;
+use tuff_core::metrics::MetricType;
+use tuff_macro::profile_mod;
+
+#[profile_mod(exclude(helper))]
+mod parser {
+    pub struct Parser;
+
+    impl Parser {
+        pub fn parse(&self, input: &str) -> usize {
+            super::parser::tokenize(input) + helper()
+        }
+    }
+
+    pub fn tokenize(input: &str) -> usize {
+        input.split_whitespace().count()
+    }
+
+    fn helper() -> usize {
+        0
+    }
+
+    pub const fn version() -> u32 {
+        1
+    }
+}
+
+fn main() {
+    Profiler::start_global(MetricType::OsClock);
+    assert_eq!(parser::Parser.parse("a b c"), 3);
+    assert_eq!(parser::version(), 1);
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let mut labels: Vec<_> = report.measurements().iter().map(|m| m.label()).collect();
+    labels.sort();
+    assert_eq!(labels, ["Parser::parse", "tokenize"]);
+}
@@ -0,0 +1,84 @@
+use tuff_core::Profiler;
+use tuff_core::metrics::MetricType;
+use tuff_macro::profile_fn;
+
+trait Shape {
+    fn area(&self) -> f64;
+
+    fn scaled(&self, factor: f64) -> f64 {
+        self.area() * factor
+    }
+}
+
+struct Square(f64);
+
+impl Shape for Square {
+    #[profile_fn]
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+
+    #[profile_fn("Square::scaled")]
+    fn scaled(&self, factor: f64) -> f64 {
+        self.area() * factor
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct circle(f64);
+
+impl Shape for circle {
+    #[profile_fn]
+    fn area(&self) -> f64 {
+        3.0 * self.0 * self.0
+    }
+}
+
+impl circle {
+    #[profile_fn]
+    fn unit() -> Self {
+        circle(1.0)
+    }
+}
+
+#[allow(non_snake_case)]
+mod Shapes {
+    #[tuff_macro::profile_fn]
+    pub fn origin() -> (f64, f64) {
+        (0.0, 0.0)
+    }
+}
+
+struct Wrapper<T>(T);
+
+impl<T: Clone> Wrapper<T> {
+    #[profile_fn]
+    pub fn get(&self) -> T {
+        self.0.clone()
+    }
+}
+
+fn main() {
+    Profiler::start_global(MetricType::OsClock);
+    let square = Square(2.0);
+    assert_eq!(square.scaled(2.0), 8.0);
+    assert_eq!(circle::unit().area(), 3.0);
+    assert_eq!(Shapes::origin(), (0.0, 0.0));
+    assert_eq!(Wrapper(3).get(), 3);
+    Profiler::stop_global();
+
+    let report = Profiler::report().unwrap();
+    let mut labels: Vec<_> = report.measurements().iter().map(|m| m.label()).collect();
+    labels.sort();
+    assert_eq!(
+        labels,
+        [
+            "Square::area",
+            "Square::scaled",
+            "Wrapper<_>::get",
+            "circle::area",
+            "circle::unit",
+            "origin"
+        ]
+    );
+}
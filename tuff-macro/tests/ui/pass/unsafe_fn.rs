@@ -0,0 +1,20 @@
+use tuff_macro::profile_fn;
+
+/// # Safety
+///
+/// `ptr` must be valid for reads.
+#[profile_fn]
+unsafe fn read(ptr: *const u32) -> u32 {
+    unsafe { *ptr }
+}
+
+#[profile_fn]
+pub unsafe extern "C" fn read_c(ptr: *const u32) -> u32 {
+    unsafe { *ptr }
+}
+
+fn main() {
+    let value = 5;
+    assert_eq!(unsafe { read(&value) }, 5);
+    assert_eq!(unsafe { read_c(&value) }, 5);
+}
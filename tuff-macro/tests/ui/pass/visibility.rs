@@ -0,0 +1,30 @@
+mod inner {
+    use tuff_macro::profile_fn;
+
+    #[profile_fn]
+    pub fn public() -> u32 {
+        1
+    }
+
+    #[profile_fn]
+    pub(crate) fn crate_visible() -> u32 {
+        2
+    }
+
+    pub mod nested {
+        use tuff_macro::profile_fn;
+
+        #[profile_fn]
+        pub(super) fn parent_visible() -> u32 {
+            3
+        }
+    }
+
+    pub fn call_nested() -> u32 {
+        nested::parent_visible()
+    }
+}
+
+fn main() {
+    assert_eq!(inner::public() + inner::crate_visible() + inner::call_nested(), 6);
+}
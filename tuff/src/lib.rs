@@ -1,3 +1,3 @@
-pub use tuff_macro::profile_fn;
+pub use tuff_macro::{profile_fn, profile_impl, profile_mod};
 
 pub use tuff_core::*;